@@ -0,0 +1,102 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::clog::Clog;
+
+// The reference must start a word, so that e-mail addresses, urls and the text
+// of existing links are left alone. It is compiled once, rather than for
+// every subject.
+fn reference_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        regex!(
+            r"(^|[\s(,;:])(?:(?P<repo>~?[\w.-]+/[\w.-]+)?#(?P<issue>\d+)|@(?P<user>[A-Za-z0-9][A-Za-z0-9_-]*)|(?P<hash>[0-9a-f]{7,40}))\b"
+        )
+    })
+}
+
+/// Turns the issue references (`#12` and `owner/repo#12`), mentions
+/// (`@user`) and short commit hashes in a subject into Markdown links,
+/// leaving code spans in backticks untouched. References which can't be linked,
+/// i.e. without a repository, are kept as they are.
+pub(crate) fn link_subject(options: &Clog, subject: &str) -> String {
+    let re = reference_regex();
+    let parts = subject.split('`').collect::<Vec<_>>();
+    // An unmatched backtick doesn't start a code span
+    let closed = parts.len() - (parts.len() + 1) % 2;
+
+    let mut out = String::with_capacity(subject.len());
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            out.push('`');
+        }
+        if i % 2 == 1 && i < closed {
+            out.push_str(part);
+        } else {
+            out.push_str(&re.replace_all(part, |caps: &Captures| link_reference(options, caps)));
+        }
+    }
+    out
+}
+
+fn link_reference(options: &Clog, caps: &Captures) -> String {
+    let text = &caps[0][caps[1].len()..];
+    let link = if let Some(issue) = caps.name("issue") {
+        let id = match caps.name("repo") {
+            Some(repo) => format!("{}#{}", repo.as_str(), issue.as_str()),
+            None => issue.as_str().to_owned(),
+        };
+        Some(options.issue_link(&id)).filter(|link| *link != id)
+    } else if let Some(user) = caps.name("user") {
+        // Link templates have no user profiles to link to
+        options
+            .link_style
+            .user_link(user.as_str(), options.repo.as_deref())
+            .filter(|_| options.link_templates.is_none())
+    } else {
+        // Only words with both digits and letters are likely to be hashes
+        let hash = &caps["hash"];
+        let is_hash = hash.bytes().any(|b| b.is_ascii_digit())
+            && hash.bytes().any(|b| b.is_ascii_alphabetic());
+        Some(options.commit_link(hash)).filter(|link| is_hash && link != hash)
+    };
+
+    match link {
+        Some(link) => format!("{}[{text}]({link})", &caps[1]),
+        None => caps[0].to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_subjects() {
+        let clog = Clog::default().repository("https://github.com/o/r");
+        assert_eq!(
+            link_subject(&clog, "regression from #120 reported by @alice"),
+            "regression from [#120](https://github.com/o/r/issues/120) reported by \
+             [@alice](https://github.com/alice)"
+        );
+        assert_eq!(
+            link_subject(&clog, "revert 1a2b3c4, see o2/r2#5"),
+            "revert [1a2b3c4](https://github.com/o/r/commit/1a2b3c4), see \
+             [o2/r2#5](https://github.com/o2/r2/issues/5)"
+        );
+        assert_eq!(
+            link_subject(&clog, "keep `#1 @bob` and mail a@b.c, deadbeef"),
+            "keep `#1 @bob` and mail a@b.c, deadbeef"
+        );
+        assert_eq!(
+            link_subject(&clog, "unmatched ` #2"),
+            "unmatched ` [#2](https://github.com/o/r/issues/2)"
+        );
+        assert_eq!(link_subject(&Clog::default(), "see #3"), "see #3");
+        assert_eq!(
+            link_subject(&Clog::default(), "revert 1a2b3c4"),
+            "revert 1a2b3c4"
+        );
+    }
+}
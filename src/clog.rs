@@ -1,32 +1,62 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::AsRef,
     env,
     fs::File,
-    io::{stdout, BufWriter, Read, Write},
+    io::{stdout, BufRead, BufReader, BufWriter, Read, Write},
     path::{Path, PathBuf},
-    process::Command,
+    process::{Command, Stdio},
     result::Result as StdResult,
 };
 
 use indexmap::IndexMap;
 use log::debug;
 use regex::Regex;
+use time::{format_description, OffsetDateTime};
 
 use crate::{
     config::RawCfg,
+    contributors::{self, Contributor},
+    diff::ChangelogDiff,
     error::{Error, Result},
-    fmt::{ChangelogFormat, FormatWriter, JsonWriter, MarkdownWriter},
-    git::{Commit, Commits},
-    link_style::LinkStyle,
+    fmt::{
+        ChangelogFormat, CommitWriter, CsvColumn, CsvWriter, FormatWriter, JsonWriter,
+        MarkdownWriter, NdjsonWriter, ReleaseInfo, TomlWriter, YamlWriter,
+    },
+    git::{self, Commit, Commits},
+    issue_tracker::IssueTracker,
+    link_style::{LinkStyle, LinkTemplates},
+    output::Output,
+    reference::{self, IssueRef},
+    release_date::{self, DateSource, TimeZone},
+    remote,
     sectionmap::SectionMap,
+    update::{self, ExistingRelease},
+    verify::{self, VerifyReport},
     DEFAULT_CONFIG_FILE,
 };
 
 fn regex_default() -> Regex { regex!(r"^([^:\(]+?)(?:\(([^\)]*?)?\))?:(.*)") }
-fn closes_regex_default() -> Regex { regex!(r"(?:Closes|Fixes|Resolves)\s((?:#(\d+)(?:,\s)?)+)") }
-fn breaks_regex_default() -> Regex { regex!(r"(?:Breaks|Broke)\s((?:#(\d+)(?:,\s)?)+)") }
+fn closes_regex_default() -> Regex { reference::reference_regex(&closes_keywords_default(), &[]) }
+fn refs_regex_default() -> Regex { reference::reference_regex(&refs_keywords_default(), &[]) }
+fn breaks_regex_default() -> Regex { reference::reference_regex(&breaks_keywords_default(), &[]) }
 fn breaking_regex_default() -> Regex { regex!(r"(?i:breaking)") }
+fn pr_regex_default() -> Regex { regex!(r"\s*\([#!](\d+)\)\s*$") }
+fn merge_regex_default() -> Regex { regex!(r"^Merge pull request #(\d+) from \S+") }
+
+fn closes_keywords_default() -> Vec<String> {
+    [
+        "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+fn refs_keywords_default() -> Vec<String> {
+    ["ref", "refs", "references", "see", "related to"]
+        .map(String::from)
+        .to_vec()
+}
+fn breaks_keywords_default() -> Vec<String> { ["breaks", "broke"].map(String::from).to_vec() }
 
 /// The base struct used to set options and interact with the library.
 #[derive(Debug, Clone)]
@@ -35,6 +65,15 @@ pub struct Clog {
     pub repo: Option<String>,
     /// The link style to used for commit and issue hyper-links
     pub link_style: LinkStyle,
+    /// URL templates for commit and issue hyper-links, which replace the
+    /// `link_style` when set
+    pub link_templates: Option<LinkTemplates>,
+    /// The git remote the `repo` and `link_style` are detected from if they are
+    /// not configured (Defaults to `origin`)
+    pub remote: String,
+    /// The external issue trackers whose issue keys are referenced by commits
+    /// alongside `#123` style issues
+    pub issue_trackers: Vec<IssueTracker>,
     /// The file to use as the old changelog data to be appended to anything new
     /// found.
     pub infile: Option<String>,
@@ -54,22 +93,89 @@ pub struct Clog {
     pub git_dir: Option<PathBuf>,
     /// The format to output the changelog in (Defaults to Markdown)
     pub out_format: ChangelogFormat,
+    /// The columns, in order, used when writing CSV output (Defaults to all
+    /// columns)
+    pub csv_columns: Vec<CsvColumn>,
+    /// What to do when the old changelog already contains a release with the
+    /// same version (Defaults to replacing it in place)
+    pub existing_release: ExistingRelease,
+    /// The name used in the `<!-- {marker}:start -->` and `<!-- {marker}:end
+    /// -->` comments delimiting where the changelog lives inside a larger
+    /// document (Defaults to `clog`)
+    pub marker: String,
+    /// Whether the previous version of an output file is kept as
+    /// `{outfile}.bak` when it is replaced (Defaults to `false`)
+    pub backup: bool,
+    /// Additional outputs to write from a single run. When any are set,
+    /// `Clog::write_changelog()` writes to these instead of `out_format`,
+    /// `infile` and `outfile`
+    pub outputs: Vec<Output>,
+    /// Whether the `MarkdownWriter` records the range of commits of each
+    /// release in a hidden comment (Defaults to `false`)
+    pub embed_range: bool,
+    /// Whether to start from the newest range recorded in the changelog when
+    /// `from` isn't set (Defaults to `false`)
+    pub from_changelog: bool,
+    /// Whether issue references, mentions and short commit hashes in subjects
+    /// are turned into links by the `MarkdownWriter` (Defaults to `false`)
+    pub autolink: bool,
+    /// Whether writers add a block listing the contributors of the release
+    /// (Defaults to `false`)
+    pub contributors: bool,
+    /// Whether contributors who didn't contribute before `from` are
+    /// highlighted (Defaults to `false`)
+    pub highlight_first_time: bool,
+    /// The patterns of names or e-mails of bots left out of the contributors,
+    /// where `*` matches any text (Defaults to `*[bot]`)
+    pub bots: Vec<String>,
+    /// Whether an "Unreleased" block is written and maintained while there is
+    /// no version (Defaults to `false`)
+    pub unreleased: bool,
+    /// Where the release date is taken from if no `date` is set (Defaults to
+    /// `DateSource::Now`)
+    pub date_source: DateSource,
+    /// An explicit release date
+    pub date: Option<OffsetDateTime>,
+    /// The format description of release dates (Defaults to
+    /// `[year]-[month]-[day]`)
+    pub date_format: String,
+    /// The time zone release dates are shown in (Defaults to UTC)
+    pub time_zone: TimeZone,
     /// The grep search pattern used to find commits we are interested in
     /// (Defaults to: "^ft|^feat|^fx|^fix|^perf|^unk|BREAKING\'")
     pub grep: String,
     /// The format of the commit output from `git log` (Defaults to:
-    /// "%H%n%s%n%b%n==END==")
+    /// "%H%n%aN%x1f%aE%x1f%aI%x1f%cN%x1f%cE%x1f%cI%n%s%n%b%n==END=="). The
+    /// line of authors and committers after the hash is optional.
     pub format: String,
     /// The working directory of the git project (typically the project
     /// directory, or parent of the `.git` directory)
     pub git_work_tree: Option<PathBuf>,
     /// The regex used to get components, aliases, and messages
     pub regex: Regex,
+    /// The keywords introducing issues a commit closes, matched regardless of
+    /// case and optionally followed by a colon (Defaults to GitHub's closing
+    /// keywords, i.e. `close`, `fixes` or `resolved`)
+    pub closes_keywords: Vec<String>,
+    /// The keywords introducing issues a commit refers to without closing
+    /// them (Defaults to `ref`, `refs`, `references`, `see` and `related to`)
+    pub refs_keywords: Vec<String>,
+    /// The keywords introducing issues a commit breaks (Defaults to `breaks`
+    /// and `broke`)
+    pub breaks_keywords: Vec<String>,
     /// The regex used to get closes issue links
     pub closes_regex: Regex,
-    /// The regex used to get closes issue links
+    /// The regex used to get referenced issue links
+    pub refs_regex: Regex,
+    /// The regex used to get breaks issue links
     pub breaks_regex: Regex,
     pub breaking_regex: Regex,
+    /// The regex used to get the pull request number at the end of a squash
+    /// merged subject, i.e. `add pagination (#482)`
+    pub pr_regex: Regex,
+    /// The regex used to get the pull request number of a merge commit, whose
+    /// subject is then taken from the first line of the body
+    pub merge_regex: Regex,
     /// Where to start looking for commits using a hash (or short hash)
     pub from: Option<String>,
     /// Where to stop looking for commits using a hash (or short hash).
@@ -110,9 +216,12 @@ impl Default for Clog {
                         .fold(String::new(), |acc, al| { acc + &format!("^{}|", al)[..] }))
                     .fold(String::new(), |acc, al| { acc + &format!("^{}|", al)[..] })
             ),
-            format: "%H%n%s%n%b%n==END==".to_string(),
+            format: format!("%H%n{}%n%s%n%b%n==END==", git::SIGNATURES_FORMAT),
             repo: None,
             link_style: LinkStyle::Github,
+            link_templates: None,
+            remote: "origin".to_owned(),
+            issue_trackers: vec![],
             version: None,
             patch_ver: false,
             subtitle: None,
@@ -123,12 +232,34 @@ impl Default for Clog {
             section_map: sections,
             component_map: HashMap::new(),
             out_format: ChangelogFormat::Markdown,
+            csv_columns: CsvColumn::all(),
+            existing_release: ExistingRelease::Replace,
+            marker: "clog".to_owned(),
+            backup: false,
+            embed_range: false,
+            from_changelog: false,
+            autolink: false,
+            contributors: false,
+            highlight_first_time: false,
+            bots: vec!["*[bot]".to_owned()],
+            unreleased: false,
+            date_source: DateSource::Now,
+            date: None,
+            date_format: "[year]-[month]-[day]".to_owned(),
+            time_zone: TimeZone::UTC,
+            outputs: vec![],
             git_dir: None,
             git_work_tree: None,
             regex: regex_default(),
+            closes_keywords: closes_keywords_default(),
+            refs_keywords: refs_keywords_default(),
+            breaks_keywords: breaks_keywords_default(),
             closes_regex: closes_regex_default(),
+            refs_regex: refs_regex_default(),
             breaks_regex: breaks_regex_default(),
             breaking_regex: breaking_regex_default(),
+            pr_regex: pr_regex_default(),
+            merge_regex: merge_regex_default(),
         }
     }
 }
@@ -139,17 +270,79 @@ impl TryFrom<RawCfg> for Clog {
     fn try_from(cfg: RawCfg) -> StdResult<Self, Self::Error> {
         let mut clog = Self {
             repo: cfg.clog.repository,
-            link_style: cfg.clog.link_style,
+            link_style: cfg.clog.link_style.unwrap_or_default(),
+            remote: cfg.clog.remote.unwrap_or_else(|| "origin".to_owned()),
             subtitle: cfg.clog.subtitle,
             infile: cfg.clog.changelog.clone().or(cfg.clog.infile),
             outfile: cfg.clog.changelog.or(cfg.clog.outfile),
             section_map: cfg.sections,
             component_map: cfg.components,
             out_format: cfg.clog.output_format,
+            csv_columns: cfg.clog.csv_columns.unwrap_or_else(CsvColumn::all),
+            existing_release: cfg.clog.existing_release,
+            marker: cfg.clog.marker.unwrap_or_else(|| "clog".to_owned()),
+            backup: cfg.clog.backup,
+            embed_range: cfg.clog.embed_range,
+            from_changelog: cfg.clog.from_changelog,
+            unreleased: cfg.clog.unreleased,
+            autolink: cfg.clog.autolink,
+            contributors: cfg.clog.contributors,
+            highlight_first_time: cfg.clog.highlight_first_time,
+            date_source: cfg.clog.date_source,
+            outputs: cfg.output.into_iter().map(Into::into).collect(),
             git_dir: cfg.clog.git_dir,
             git_work_tree: cfg.clog.git_work_tree,
             ..Self::default()
         };
+        if clog.repo.is_none() {
+            clog.repo = clog.get_remote_url();
+        }
+        if cfg.clog.link_style.is_none() {
+            if let Some(style) = clog.repo.as_deref().and_then(remote::infer_link_style) {
+                clog.link_style = style;
+            }
+        }
+        if let Some(links) = cfg.links {
+            if cfg.clog.link_style.is_some() {
+                return Err(Error::LinkConflict);
+            }
+            links.validate()?;
+            if clog.repo.is_none() && links.uses_repo() {
+                return Err(Error::LinkTemplate {
+                    template: "{repo}".to_owned(),
+                    reason: "no repository is set".to_owned(),
+                });
+            }
+            clog.link_templates = Some(links);
+        }
+        for tracker in cfg.issue_trackers {
+            clog.issue_trackers
+                .push(IssueTracker::new(&tracker.pattern, tracker.url)?);
+        }
+        if let Some(keywords) = cfg.keywords.closes {
+            clog.closes_keywords = keywords;
+        }
+        if let Some(keywords) = cfg.keywords.refs {
+            clog.refs_keywords = keywords;
+        }
+        if let Some(keywords) = cfg.keywords.breaks {
+            clog.breaks_keywords = keywords;
+        }
+        clog.build_reference_regexes();
+        if let Some(bots) = cfg.clog.bots {
+            clog.bots = bots;
+        }
+        if let Some(tz) = cfg.clog.time_zone {
+            clog.time_zone = tz.parse()?;
+        }
+        if let Some(date) = cfg.clog.date {
+            clog.date = Some(release_date::parse_date(&date, clog.time_zone)?);
+        }
+        if let Some(format) = cfg.clog.date_format {
+            // Fail early on an invalid format rather than when writing
+            format_description::parse_borrowed::<1>(&format).map_err(time::Error::from)?;
+            clog.date_format = format;
+        }
         if cfg.clog.from_latest_tag {
             clog.from = Some(clog.get_latest_tag()?);
         }
@@ -247,22 +440,26 @@ impl Clog {
             gd.push(".git");
             git_dir = Some(gd);
         }
-        Ok(Clog {
-            git_dir,
-            git_work_tree,
-            ..Clog::try_config_file(&cfg)?
-        })
+        Clog::try_config_file(&cfg, git_dir, git_work_tree)
     }
 
     // Try and create a clog object from a config file
-    fn try_config_file(cfg_file: &Path) -> Result<Self> {
+    fn try_config_file(
+        cfg_file: &Path,
+        git_dir: Option<PathBuf>,
+        git_work_tree: Option<PathBuf>,
+    ) -> Result<Self> {
         debug!("Trying to use config file: {:?}", cfg_file);
         let mut toml_f = File::open(cfg_file)?;
         let mut toml_s = String::with_capacity(100);
 
         toml_f.read_to_string(&mut toml_s)?;
 
-        let cfg: RawCfg = toml::from_str(&toml_s[..])?;
+        let mut cfg: RawCfg = toml::from_str(&toml_s[..])?;
+        // The git directories must be known before the config is converted, as
+        // it may need to look up the latest tag or the remote
+        cfg.clog.git_dir = git_dir;
+        cfg.clog.git_work_tree = git_work_tree;
         cfg.try_into()
     }
 
@@ -332,6 +529,117 @@ impl Clog {
         self
     }
 
+    /// Sets URL templates for hyper-links to forges without a built-in
+    /// `LinkStyle`, which replace the link style
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, LinkTemplates};
+    /// let templates = LinkTemplates {
+    ///     commit: Some("{repo}/c/{hash}".to_owned()),
+    ///     ..LinkTemplates::default()
+    /// };
+    /// let clog = Clog::new().unwrap().link_templates(templates);
+    /// ```
+    #[must_use]
+    pub fn link_templates(mut self, t: LinkTemplates) -> Clog {
+        self.link_templates = Some(t);
+        self
+    }
+
+    /// Adds an external issue tracker, whose issue keys are then found in
+    /// `Closes` and `Breaks` references and linked to the tracker
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, IssueTracker};
+    /// let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+    /// let clog = Clog::new().unwrap().issue_tracker(jira);
+    /// ```
+    #[must_use]
+    pub fn issue_tracker(mut self, tracker: IssueTracker) -> Clog {
+        self.issue_trackers.push(tracker);
+        self.build_reference_regexes();
+        self
+    }
+
+    /// Sets the keywords introducing issues a commit closes, i.e. `Fixes: #12`
+    ///
+    /// **NOTE:** Anything set here will override anything in a configuration
+    /// TOML file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().closes_keywords(["closes", "fixes"]);
+    /// ```
+    #[must_use]
+    pub fn closes_keywords<I, S>(mut self, keywords: I) -> Clog
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.closes_keywords = keywords.into_iter().map(Into::into).collect();
+        self.build_reference_regexes();
+        self
+    }
+
+    /// Sets the keywords introducing issues a commit refers to without closing
+    /// them, i.e. `Refs #12`
+    ///
+    /// **NOTE:** Anything set here will override anything in a configuration
+    /// TOML file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().refs_keywords(["refs", "part of"]);
+    /// ```
+    #[must_use]
+    pub fn refs_keywords<I, S>(mut self, keywords: I) -> Clog
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.refs_keywords = keywords.into_iter().map(Into::into).collect();
+        self.build_reference_regexes();
+        self
+    }
+
+    /// Sets the keywords introducing issues a commit breaks, i.e. `Breaks #12`
+    ///
+    /// **NOTE:** Anything set here will override anything in a configuration
+    /// TOML file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().breaks_keywords(["breaks"]);
+    /// ```
+    #[must_use]
+    pub fn breaks_keywords<I, S>(mut self, keywords: I) -> Clog
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.breaks_keywords = keywords.into_iter().map(Into::into).collect();
+        self.build_reference_regexes();
+        self
+    }
+
+    /// Rebuilds the regexes finding issue references from the keywords and
+    /// issue trackers
+    fn build_reference_regexes(&mut self) {
+        self.closes_regex = reference::reference_regex(&self.closes_keywords, &self.issue_trackers);
+        self.refs_regex = reference::reference_regex(&self.refs_keywords, &self.issue_trackers);
+        self.breaks_regex = reference::reference_regex(&self.breaks_keywords, &self.issue_trackers);
+    }
+
     /// Sets the version for the release
     ///
     /// **NOTE:** Anything set here will override anything in a configuration
@@ -523,225 +831,1017 @@ impl Clog {
         self
     }
 
-    /// Retrieves a `Vec<Commit>` of only commits we care about.
+    /// The columns, in order, to use when writing CSV output (Defaults to all
+    /// columns)
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let commits = clog.get_commits();
+    /// # use clog::{fmt::CsvColumn, Clog};
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .csv_columns(vec![CsvColumn::Version, CsvColumn::Subject]);
     /// ```
-    pub fn get_commits(&self) -> Result<Commits> {
-        let range = if let Some(from) = self.from.as_ref() {
-            format!("{from}..{}", self.to)
-        } else {
-            "HEAD".to_owned()
-        };
-
-        let output = Command::new("git")
-            .arg(&self.get_git_dir()[..])
-            .arg(&self.get_git_work_tree()[..])
-            .arg("log")
-            .arg("-E")
-            .arg(&format!("--grep={}", self.grep))
-            .arg(&format!("--format={}", self.format))
-            .arg(&range)
-            .output()?;
-
-        Ok(String::from_utf8_lossy(&output.stdout)
-            .split("\n==END==\n")
-            .filter_map(|commit_str| self.parse_raw_commit(commit_str).ok())
-            .filter(|entry| entry.commit_type != "Unknown")
-            .collect())
-    }
-
-    #[doc(hidden)]
-    pub fn parse_raw_commit(&self, commit_str: &str) -> Result<Commit> {
-        let mut lines = commit_str.lines();
-        let hash = lines.next().unwrap_or_default();
-
-        let (subject, component, commit_type) =
-            match lines.next().and_then(|s| self.regex.captures(s)) {
-                Some(caps) => {
-                    let section = caps.get(1).map(|c| c.as_str()).unwrap_or_default();
-                    let commit_type = self
-                        .section_for(section)
-                        .ok_or(Error::UnknownComponent(section.into()))?;
-                    let component = caps.get(2).map(|component| {
-                        let component = component.as_str();
-                        match self.component_for(component) {
-                            Some(alias) => alias.clone(),
-                            None => component.to_owned(),
-                        }
-                    });
-                    let subject = caps.get(3).map(|c| c.as_str());
-                    (subject, component, commit_type)
-                }
-                None => (
-                    None,
-                    None,
-                    self.section_for("unk")
-                        .ok_or(Error::UnknownComponent("unk".into()))?,
-                ),
-            };
-        let mut closes = vec![];
-        let mut breaks = vec![];
-        for line in lines {
-            if let Some(caps) = self.closes_regex.captures(line) {
-                if let Some(cap) = caps.get(2) {
-                    closes.push(cap.as_str().to_owned());
-                }
-            }
-            if let Some(caps) = self.breaks_regex.captures(line) {
-                if let Some(cap) = caps.get(2) {
-                    breaks.push(cap.as_str().to_owned());
-                }
-            } else if self.breaking_regex.captures(line).is_some() {
-                breaks.push(String::new());
-            }
-        }
-
-        Ok(Commit {
-            hash: hash.to_string(),
-            subject: subject.unwrap().to_owned(),
-            component: component.unwrap_or_default(),
-            closes,
-            breaks,
-            commit_type: commit_type.to_string(),
-        })
+    #[must_use]
+    pub fn csv_columns<I: IntoIterator<Item = CsvColumn>>(mut self, c: I) -> Clog {
+        self.csv_columns = c.into_iter().collect();
+        self
     }
 
-    /// Retrieves the latest tag from the git directory
+    /// Sets what to do when the old changelog already contains a release with
+    /// the same version (Defaults to `ExistingRelease::Replace`)
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let tag = clog.get_latest_tag().unwrap();
+    /// # use clog::{Clog, ExistingRelease};
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .existing_release(ExistingRelease::Error);
     /// ```
-    pub fn get_latest_tag(&self) -> Result<String> {
-        let output = Command::new("git")
-            .arg(&self.get_git_dir()[..])
-            .arg(&self.get_git_work_tree()[..])
-            .arg("rev-list")
-            .arg("--tags")
-            .arg("--max-count=1")
-            .output()?;
-        let buf = String::from_utf8_lossy(&output.stdout);
-
-        Ok(buf.trim_matches('\n').to_owned())
+    #[must_use]
+    pub fn existing_release(mut self, e: ExistingRelease) -> Clog {
+        self.existing_release = e;
+        self
     }
 
-    /// Retrieves the latest tag version from the git directory
+    /// Sets the name used in the comments marking where the changelog lives
+    /// inside a larger document such as a `README` (Defaults to `clog`). When
+    /// the old changelog contains `<!-- {marker}:start -->`, only the region
+    /// between it and `<!-- {marker}:end -->` is updated
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let tag_ver = clog.get_latest_tag_ver();
+    /// let clog = Clog::new().unwrap().marker("changelog");
     /// ```
-    pub fn get_latest_tag_ver(&self) -> String {
-        let output = Command::new("git")
-            .arg(&self.get_git_dir()[..])
-            .arg(&self.get_git_work_tree()[..])
-            .arg("describe")
-            .arg("--tags")
-            .arg("--abbrev=0")
-            .output()
-            .unwrap_or_else(|e| panic!("Failed to run 'git describe' with error: {}", e));
-
-        String::from_utf8_lossy(&output.stdout).into_owned()
+    #[must_use]
+    pub fn marker<S: Into<String>>(mut self, m: S) -> Clog {
+        self.marker = m.into();
+        self
     }
 
-    /// Retrieves the hash of the most recent commit from the git directory
-    /// (i.e. HEAD)
+    /// Sets whether the previous version of an output file is kept as
+    /// `{outfile}.bak` when it is replaced (Defaults to `false`)
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let head_hash = clog.get_last_commit();
+    /// let clog = Clog::new().unwrap().backup(true);
     /// ```
-    pub fn get_last_commit(&self) -> String {
-        let output = Command::new("git")
-            .arg(&self.get_git_dir()[..])
-            .arg(&self.get_git_work_tree()[..])
-            .arg("rev-parse")
-            .arg("HEAD")
-            .output()
-            .unwrap_or_else(|e| panic!("Failed to run 'git rev-parse' with error: {}", e));
-
-        String::from_utf8_lossy(&output.stdout).into_owned()
-    }
-
-    fn get_git_work_tree(&self) -> String {
-        // Check if user supplied a local git dir and working tree
-        if self.git_work_tree.is_none() && self.git_dir.is_none() {
-            // None was provided
-            "".to_owned()
-        } else if self.git_dir.is_some() {
-            // user supplied both
-            format!(
-                "--work-tree={}",
-                self.git_work_tree.clone().unwrap().to_str().unwrap()
-            )
-        } else {
-            // user only supplied a working tree i.e. /home/user/mycode
-            let mut w = self.git_work_tree.clone().unwrap();
-            w.pop();
-            format!("--work-tree={}", w.to_str().unwrap())
-        }
-    }
-
-    fn get_git_dir(&self) -> String {
-        // Check if user supplied a local git dir and working tree
-        if self.git_dir.is_none() && self.git_work_tree.is_none() {
-            // None was provided
-            "".to_owned()
-        } else if self.git_work_tree.is_some() {
-            // user supplied both
-            format!(
-                "--git-dir={}",
-                self.git_dir.clone().unwrap().to_str().unwrap()
-            )
-        } else {
-            // user only supplied a git dir i.e. /home/user/mycode/.git
-            let mut g = self.git_dir.clone().unwrap();
-            g.push(".git");
-            format!("--git-dir={}", g.to_str().unwrap())
-        }
+    #[must_use]
+    pub fn backup(mut self, b: bool) -> Clog {
+        self.backup = b;
+        self
     }
 
-    /// Retrieves the section title for a given alias
+    /// Sets whether the `MarkdownWriter` records the range of commits of each
+    /// release in a hidden `<!-- clog: range=from..to -->` comment
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let section = clog.section_for("feat");
-    /// assert_eq!(Some("Features"), section);
+    /// let clog = Clog::new().unwrap().embed_range(true);
     /// ```
-    pub fn section_for(&self, alias: &str) -> Option<&str> {
-        self.section_map
-            .iter()
-            .find(|&(_, v)| v.iter().any(|s| s == alias))
-            .map(|(k, _)| &**k)
+    #[must_use]
+    pub fn embed_range(mut self, e: bool) -> Clog {
+        self.embed_range = e;
+        self
     }
 
-    /// Retrieves the full component name for a given alias (if one is defined)
+    /// Sets whether to start from the end of the newest range recorded in the
+    /// changelog (see `Clog::embed_range()`) when `from` isn't set, so only
+    /// the commits since the last update of the changelog are picked up
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use clog::Clog;
-    /// let clog = Clog::new().unwrap();
-    /// let component = clog.component_for("will_be_none");
-    /// assert_eq!(None, component);
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .changelog("changelog.md")
+    ///     .from_changelog(true);
+    /// ```
+    #[must_use]
+    pub fn from_changelog(mut self, f: bool) -> Clog {
+        self.from_changelog = f;
+        self
+    }
+
+    /// Sets whether the `MarkdownWriter` writes an "Unreleased" block while
+    /// there is no version, which replaces the previous Unreleased block on
+    /// each run. Once a version is set, the Unreleased block is replaced by
+    /// the versioned release.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .changelog("changelog.md")
+    ///     .unreleased(true);
+    /// ```
+    #[must_use]
+    pub fn unreleased(mut self, u: bool) -> Clog {
+        self.unreleased = u;
+        self
+    }
+
+    /// Sets whether the `MarkdownWriter` links the issue references (`#12`
+    /// and `owner/repo#12`), mentions (`@user`) and short commit hashes in
+    /// subjects. Code spans in backticks are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .repository("https://github.com/clog-tool/clog-lib")
+    ///     .autolink(true);
+    /// ```
+    #[must_use]
+    pub fn autolink(mut self, a: bool) -> Clog {
+        self.autolink = a;
+        self
+    }
+
+    /// Sets whether writers add a block listing the contributors of the
+    /// release, see `Clog::get_contributors()`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().contributors(true);
+    /// ```
+    #[must_use]
+    pub fn contributors(mut self, c: bool) -> Clog {
+        self.contributors = c;
+        self
+    }
+
+    /// Sets whether contributors who didn't contribute to the history before
+    /// `from` are highlighted as first time contributors
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .contributors(true)
+    ///     .highlight_first_time(true);
+    /// ```
+    #[must_use]
+    pub fn highlight_first_time(mut self, h: bool) -> Clog {
+        self.highlight_first_time = h;
+        self
+    }
+
+    /// Sets the patterns of names or e-mails of bots, which are left out of
+    /// the contributors. A `*` matches any text, and matching ignores case.
+    ///
+    /// **NOTE:** Anything set here will override anything in a configuration
+    /// TOML file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .contributors(true)
+    ///     .bots(["*[bot]", "renovate*"]);
+    /// ```
+    #[must_use]
+    pub fn bots<I, S>(mut self, bots: I) -> Clog
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.bots = bots.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets where the release date is taken from when no explicit `date` is
+    /// set
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, DateSource};
+    /// let clog = Clog::new().unwrap().date_source(DateSource::Commit);
+    /// ```
+    #[must_use]
+    pub fn date_source(mut self, d: DateSource) -> Clog {
+        self.date_source = d;
+        self
+    }
+
+    /// Sets an explicit release date
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// # use time::OffsetDateTime;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .date(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap());
+    /// ```
+    #[must_use]
+    pub fn date(mut self, d: OffsetDateTime) -> Clog {
+        self.date = Some(d);
+        self
+    }
+
+    /// Sets the format description of release dates, see
+    /// <https://time-rs.github.io/book/api/format-description.html>
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .date_format("[month repr:long] [day], [year]");
+    /// ```
+    #[must_use]
+    pub fn date_format<S: Into<String>>(mut self, f: S) -> Clog {
+        self.date_format = f.into();
+        self
+    }
+
+    /// Sets the time zone release dates are shown in, either a named time
+    /// zone or a fixed `time::UtcOffset`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, TimeZone};
+    /// # use time::UtcOffset;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .time_zone("America/New_York".parse::<TimeZone>().unwrap());
+    ///
+    /// let clog = clog.time_zone(UtcOffset::from_hms(2, 0, 0).unwrap());
+    /// ```
+    #[must_use]
+    pub fn time_zone<T: Into<TimeZone>>(mut self, tz: T) -> Clog {
+        self.time_zone = tz.into();
+        self
+    }
+
+    /// Adds an output to write the changelog to. When any outputs are added,
+    /// `Clog::write_changelog()` collects the commits once and writes every
+    /// output, instead of using `Clog::output_format()`, `Clog::infile()` and
+    /// `Clog::outfile()`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{fmt::ChangelogFormat, Clog, Output};
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .output(Output::new(ChangelogFormat::Markdown).changelog("CHANGELOG.md"))
+    ///     .output(Output::new(ChangelogFormat::Json).outfile("changelog.json"));
+    /// ```
+    #[must_use]
+    pub fn output(mut self, o: Output) -> Clog {
+        self.outputs.push(o);
+        self
+    }
+
+    /// Retrieves a `Vec<Commit>` of only commits we care about.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let commits = clog.get_commits();
+    /// ```
+    pub fn get_commits(&self) -> Result<Commits> {
+        let mut commits = vec![];
+        self.stream_commits(|commit| {
+            commits.push(commit);
+            Ok(())
+        })?;
+        Ok(commits)
+    }
+
+    /// Calls a closure with each commit we care about as soon as it has been
+    /// parsed from the `git log` output, without collecting them all into
+    /// memory first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// clog.stream_commits(|commit| {
+    ///     println!("{}", commit.subject);
+    ///     Ok(())
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn stream_commits<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(Commit) -> Result<()>,
+    {
+        let range = self.get_log_range();
+
+        let mut child = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("log")
+            .arg("-E")
+            .arg(format!("--grep={}", self.grep))
+            .arg(format!("--format={}", self.format))
+            .arg(&range)
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        // unwrap because stdout was requested as piped above
+        let mut reader = BufReader::new(child.stdout.take().unwrap());
+        let mut line = Vec::new();
+        let mut commit_str = String::new();
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.trim_end_matches('\n') == "==END==" {
+                if let Ok(entry) = self.parse_raw_commit(commit_str.trim_start_matches('\n')) {
+                    if entry.commit_type != "Unknown" {
+                        f(entry)?;
+                    }
+                }
+                commit_str.clear();
+            } else {
+                commit_str.push_str(&line);
+            }
+        }
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(Error::Git(format!("git log {range} exited with {status}")));
+        }
+
+        Ok(())
+    }
+
+    #[doc(hidden)]
+    pub fn parse_raw_commit(&self, commit_str: &str) -> Result<Commit> {
+        let mut lines = commit_str.lines().peekable();
+        let hash = lines.next().unwrap_or_default();
+
+        let (author, committer) = match lines.peek().and_then(|l| git::parse_signatures(l)) {
+            Some(signatures) => {
+                lines.next();
+                signatures
+            }
+            None => (None, None),
+        };
+
+        let mut pr = None;
+        let mut subject_line = lines.next();
+        if let Some(caps) = subject_line.and_then(|s| self.merge_regex.captures(s)) {
+            pr = Some(caps[1].to_owned());
+            // The title of the pull request is the first line of the body
+            subject_line = lines.by_ref().find(|l| !l.trim().is_empty());
+        }
+
+        let (subject, component, commit_type, raw_commit_type) =
+            match subject_line.and_then(|s| self.regex.captures(s)) {
+                Some(caps) => {
+                    let section = caps.get(1).map(|c| c.as_str()).unwrap_or_default();
+                    let commit_type = self
+                        .section_for(section)
+                        .ok_or(Error::UnknownComponent(section.into()))?;
+                    let component = caps.get(2).map(|component| {
+                        let component = component.as_str();
+                        match self.component_for(component) {
+                            Some(alias) => alias.clone(),
+                            None => component.to_owned(),
+                        }
+                    });
+                    let subject = caps.get(3).map(|c| c.as_str());
+                    (subject, component, commit_type, section)
+                }
+                None => (
+                    None,
+                    None,
+                    self.section_for("unk")
+                        .ok_or(Error::UnknownComponent("unk".into()))?,
+                    "unk",
+                ),
+            };
+        let subject = subject.map(|s| match self.pr_regex.captures(s) {
+            Some(caps) => {
+                pr.get_or_insert_with(|| caps[1].to_owned());
+                &s[..caps.get(0).map_or(s.len(), |m| m.start())]
+            }
+            None => s,
+        });
+
+        let mut closes = vec![];
+        let mut refs = vec![];
+        let mut breaks = vec![];
+        for line in lines {
+            reference::extract_into(&self.closes_regex, line, &mut closes);
+            reference::extract_into(&self.refs_regex, line, &mut refs);
+            if !reference::extract_into(&self.breaks_regex, line, &mut breaks)
+                && self.breaking_regex.is_match(line)
+            {
+                breaks.push(String::new());
+            }
+        }
+
+        Ok(Commit {
+            hash: hash.to_string(),
+            subject: subject.unwrap_or_default().to_owned(),
+            component: component.unwrap_or_default(),
+            closes,
+            refs,
+            breaks,
+            author,
+            committer,
+            pr,
+            commit_type: commit_type.to_string(),
+            raw_commit_type: raw_commit_type.to_string(),
+        })
+    }
+
+    /// Retrieves the latest tag from the git directory
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let tag = clog.get_latest_tag().unwrap();
+    /// ```
+    pub fn get_latest_tag(&self) -> Result<String> {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("rev-list")
+            .arg("--tags")
+            .arg("--max-count=1")
+            .output()?;
+        let buf = String::from_utf8_lossy(&output.stdout);
+
+        Ok(buf.trim_matches('\n').to_owned())
+    }
+
+    /// Retrieves the end of the newest range of commits recorded in the
+    /// changelog (i.e. the infile, or the outfile if there is none) by the
+    /// `MarkdownWriter`, not counting the range of an Unreleased block
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().changelog("changelog.md");
+    /// let last = clog.get_last_changelog_commit();
+    /// ```
+    pub fn get_last_changelog_commit(&self) -> Option<String> {
+        let cl = self.infile.as_ref().or(self.outfile.as_ref())?;
+        let mut contents = String::new();
+        File::open(cl)
+            .and_then(|mut f| f.read_to_string(&mut contents))
+            .ok()?;
+
+        update::last_range_end(&contents).map(ToOwned::to_owned)
+    }
+
+    /// Retrieves the commit to start from, which is `from`, or the end of the
+    /// newest range recorded in the changelog if `from_changelog` is set
+    fn get_from(&self) -> Option<String> {
+        self.from.clone().or_else(|| {
+            self.from_changelog
+                .then(|| self.get_last_changelog_commit())
+                .flatten()
+        })
+    }
+
+    /// The range of commits passed to `git log`
+    fn get_log_range(&self) -> String {
+        if let Some(from) = self.get_from() {
+            format!("{from}..{}", self.to)
+        } else {
+            "HEAD".to_owned()
+        }
+    }
+
+    /// Retrieves the unique contributors of the release, i.e. the authors and
+    /// co-authors (from `Co-authored-by:` trailers) of every commit in the
+    /// range, resolved through the repository's `.mailmap`. Contributors
+    /// matching `bots` are left out. With `highlight_first_time`, contributors
+    /// who don't appear in the history before `from` are marked as first time
+    /// contributors.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .from("v1.2.0")
+    ///     .highlight_first_time(true);
+    /// let contributors = clog.get_contributors().unwrap();
+    /// ```
+    pub fn get_contributors(&self) -> Result<Vec<Contributor>> {
+        let mut contributors =
+            contributors::unique(self.get_contacts(&self.get_log_range())?, &self.bots);
+        if let (true, Some(from)) = (self.highlight_first_time, self.get_from()) {
+            let previous = self
+                .get_contacts(&from)?
+                .iter()
+                .map(Contributor::key)
+                .collect::<HashSet<_>>();
+            for contributor in &mut contributors {
+                contributor.first_time = !previous.contains(&contributor.key());
+            }
+        }
+
+        Ok(contributors)
+    }
+
+    /// Lists the authors and co-authors of the commits in a range, including
+    /// duplicates
+    fn get_contacts(&self, range: &str) -> Result<Vec<Contributor>> {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("log")
+            .arg(format!("--format={}", contributors::CONTACTS_FORMAT))
+            .arg(range)
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(format!(
+                "git log {range} exited with {}",
+                output.status
+            )));
+        }
+
+        let mut authors = vec![];
+        let mut co_authors = HashSet::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            match line.strip_prefix(contributors::AUTHOR_MARKER) {
+                // Authors are already resolved through the mailmap by git
+                Some(author) => authors.extend(contributors::parse_contact(author)),
+                None if !line.trim().is_empty() => {
+                    co_authors.insert(line.trim().to_owned());
+                }
+                None => {}
+            }
+        }
+        authors.extend(self.check_mailmap(co_authors)?);
+
+        Ok(authors)
+    }
+
+    /// Resolves contacts such as `Jane Doe <jane@example.com>` through the
+    /// repository's `.mailmap`
+    fn check_mailmap(&self, contacts: HashSet<String>) -> Result<Vec<Contributor>> {
+        // `git check-mailmap` fails without any contacts, and on contacts
+        // without an e-mail address
+        let contacts = contacts
+            .iter()
+            .filter(|c| contributors::parse_contact(c).is_some())
+            .collect::<Vec<_>>();
+        if contacts.is_empty() {
+            return Ok(vec![]);
+        }
+        let work_tree = self.get_git_work_tree();
+        let mut cmd = Command::new("git");
+        // Unlike `git log`, `check-mailmap` only reads the `.mailmap` of the
+        // working tree it's run in
+        if let Some(dir) = work_tree.strip_prefix("--work-tree=") {
+            cmd.current_dir(dir);
+        }
+        let output = cmd
+            .arg(&self.get_git_dir()[..])
+            .arg(&work_tree[..])
+            .arg("check-mailmap")
+            .args(contacts)
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(format!(
+                "git check-mailmap exited with {}",
+                output.status
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(contributors::parse_contact)
+            .collect())
+    }
+
+    /// Resolves the range of commits the changelog is generated from to full
+    /// commit hashes, where the start is empty if there is none
+    pub(crate) fn get_range_hashes(&self) -> Result<(String, String)> {
+        let from = match self.get_from() {
+            Some(from) => self.rev_parse(&from)?,
+            None => String::new(),
+        };
+        Ok((from, self.rev_parse(&self.to)?))
+    }
+
+    fn rev_parse(&self, rev: &str) -> Result<String> {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("rev-parse")
+            .arg("--verify")
+            .arg(format!("{rev}^{{commit}}"))
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(format!(
+                "git rev-parse {rev} exited with {}",
+                output.status
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    /// Retrieves the formatted date of the release, taken from the explicit
+    /// `date` or from the `date_source`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, DateSource};
+    /// let clog = Clog::new().unwrap().date_source(DateSource::Tag);
+    /// let date = clog.get_release_date().unwrap();
+    /// ```
+    pub fn get_release_date(&self) -> Result<String> {
+        let date = match (self.date, self.date_source) {
+            (Some(date), _) => date,
+            (None, DateSource::Now) => {
+                release_date::source_date_epoch()?.unwrap_or_else(OffsetDateTime::now_utc)
+            }
+            (None, DateSource::Tag) => match self.get_tag_date()? {
+                Some(date) => date,
+                None => self.get_commit_date(&self.to)?,
+            },
+            (None, DateSource::Commit) => self.get_commit_date(&self.to)?,
+        };
+        self.format_date(date)
+    }
+
+    /// Formats a date with `date_format` in the `time_zone`
+    pub(crate) fn format_date(&self, date: OffsetDateTime) -> Result<String> {
+        let format = format_description::parse_borrowed::<1>(&self.date_format)
+            .map_err(time::Error::from)?;
+
+        self.time_zone
+            .convert(date)
+            .format(&format)
+            .map_err(Into::into)
+    }
+
+    /// Gets the hyperlink url of an issue, using the first issue tracker whose
+    /// pattern matches the issue, or the link style otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, IssueTracker};
+    /// let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .repository("https://github.com/clog-tool/clog-lib")
+    ///     .issue_tracker(jira);
+    ///
+    /// assert_eq!(
+    ///     clog.issue_link("PROJ-12"),
+    ///     "https://jira.example/browse/PROJ-12"
+    /// );
+    /// assert_eq!(
+    ///     clog.issue_link("12"),
+    ///     "https://github.com/clog-tool/clog-lib/issues/12"
+    /// );
+    /// ```
+    pub fn issue_link(&self, issue: &str) -> String {
+        match self.issue_trackers.iter().find(|t| t.matches(issue)) {
+            Some(tracker) => tracker.issue_link(issue),
+            None => match &self.link_templates {
+                Some(templates) => {
+                    templates.reference_link(IssueRef::parse(issue), self.repo.as_deref())
+                }
+                None => self
+                    .link_style
+                    .reference_link(IssueRef::parse(issue), self.repo.as_deref()),
+            },
+        }
+    }
+
+    /// Gets the hyperlink url of a commit, using the link templates if there
+    /// are any, or the link style otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .repository("https://github.com/clog-tool/clog-lib");
+    ///
+    /// assert_eq!(
+    ///     clog.commit_link("0123456789abcdef"),
+    ///     "https://github.com/clog-tool/clog-lib/commit/0123456789abcdef"
+    /// );
+    /// ```
+    pub fn commit_link(&self, hash: &str) -> String {
+        match &self.link_templates {
+            Some(templates) => templates.commit_link(hash, self.repo.as_deref()),
+            None => self.link_style.commit_link(hash, self.repo.as_deref()),
+        }
+    }
+
+    /// Gets the hyperlink url of a pull request, using the link templates if
+    /// there are any, or the link style otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .repository("https://github.com/clog-tool/clog-lib");
+    ///
+    /// assert_eq!(
+    ///     clog.pr_link("482"),
+    ///     "https://github.com/clog-tool/clog-lib/pull/482"
+    /// );
+    /// ```
+    pub fn pr_link(&self, pr: &str) -> String {
+        match &self.link_templates {
+            Some(templates) => templates.pr_link(pr, self.repo.as_deref()),
+            None => self.link_style.pr_link(pr, self.repo.as_deref()),
+        }
+    }
+
+    /// Gets the text shown for an issue, which is `#12` for issues of the
+    /// repository, `owner/repo#12` for other repositories, `!12` for merge
+    /// requests and the issue key (i.e. `PROJ-12`) for issue trackers
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// assert_eq!(clog.issue_label("12"), "#12");
+    /// ```
+    pub fn issue_label(&self, issue: &str) -> String {
+        if self.issue_trackers.iter().any(|t| t.matches(issue)) {
+            issue.to_owned()
+        } else {
+            IssueRef::parse(issue).to_string()
+        }
+    }
+
+    /// Gets the text shown for a pull request, which is `!17` for GitLab merge
+    /// requests and `#17` otherwise, including forges linked with
+    /// `link_templates`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, LinkStyle};
+    /// let clog = Clog::new().unwrap().link_style(LinkStyle::Gitlab);
+    ///
+    /// assert_eq!(clog.pr_label("17"), "!17");
+    /// ```
+    pub fn pr_label(&self, pr: &str) -> String {
+        match &self.link_templates {
+            Some(_) => format!("#{pr}"),
+            None => self.link_style.pr_label(pr),
+        }
+    }
+
+    /// Retrieves the hyperlink url to a view comparing the start and end of the
+    /// range of commits, or `None` if there is no start or repository. An end
+    /// of `HEAD` is resolved to its commit hash so the link stays valid.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new()
+    ///     .unwrap()
+    ///     .repository("https://github.com/clog-tool/clog-lib")
+    ///     .from("v1.2.0")
+    ///     .to("v1.3.0");
+    /// let link = clog.get_compare_link().unwrap();
+    /// ```
+    pub fn get_compare_link(&self) -> Result<Option<String>> {
+        let (Some(repo), Some(from)) = (self.repo.as_deref(), self.get_from()) else {
+            return Ok(None);
+        };
+        let to = if self.to == "HEAD" {
+            self.rev_parse(&self.to)?
+        } else {
+            self.to.clone()
+        };
+
+        Ok(match &self.link_templates {
+            Some(templates) => templates.compare_link(&from, &to, Some(repo)),
+            None => self.link_style.compare_link(&*from, &*to, Some(repo)),
+        })
+    }
+
+    /// Gets the date of the first existing tag out of `to`, the version, and
+    /// the version prefixed with `v`
+    fn get_tag_date(&self) -> Result<Option<OffsetDateTime>> {
+        let version = self.version.as_deref().filter(|v| !v.is_empty());
+        let candidates = std::iter::once(self.to.clone())
+            .chain(version.map(str::to_owned))
+            .chain(version.map(|v| format!("v{v}")));
+        for tag in candidates {
+            let output = Command::new("git")
+                .arg(&self.get_git_dir()[..])
+                .arg(&self.get_git_work_tree()[..])
+                .arg("for-each-ref")
+                .arg("--format=%(creatordate:unix)")
+                .arg(format!("refs/tags/{tag}"))
+                .output()?;
+            let buf = String::from_utf8_lossy(&output.stdout);
+            if let Some(secs) = buf.lines().next().filter(|l| !l.is_empty()) {
+                return release_date::parse_timestamp(secs).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Gets the committer date of a commit
+    fn get_commit_date(&self, rev: &str) -> Result<OffsetDateTime> {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("log")
+            .arg("-1")
+            .arg("--format=%ct")
+            .arg(rev)
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::Git(format!(
+                "git log {rev} exited with {}",
+                output.status
+            )));
+        }
+
+        release_date::parse_timestamp(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Retrieves the url of the web interface of the configured git `remote`,
+    /// i.e. `https://github.com/owner/repo` for a remote of
+    /// `git@github.com:owner/repo.git`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let url = clog.get_remote_url();
+    /// ```
+    pub fn get_remote_url(&self) -> Option<String> {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("config")
+            .arg("--get")
+            .arg(format!("remote.{}.url", self.remote))
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            debug!("remote {} has no url", self.remote);
+            return None;
+        }
+
+        remote::web_url(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Retrieves the latest tag version from the git directory
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let tag_ver = clog.get_latest_tag_ver();
+    /// ```
+    pub fn get_latest_tag_ver(&self) -> String {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("describe")
+            .arg("--tags")
+            .arg("--abbrev=0")
+            .output()
+            .unwrap_or_else(|e| panic!("Failed to run 'git describe' with error: {}", e));
+
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// Retrieves the hash of the most recent commit from the git directory
+    /// (i.e. HEAD)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let head_hash = clog.get_last_commit();
+    /// ```
+    pub fn get_last_commit(&self) -> String {
+        let output = Command::new("git")
+            .arg(&self.get_git_dir()[..])
+            .arg(&self.get_git_work_tree()[..])
+            .arg("rev-parse")
+            .arg("HEAD")
+            .output()
+            .unwrap_or_else(|e| panic!("Failed to run 'git rev-parse' with error: {}", e));
+
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn get_git_work_tree(&self) -> String {
+        // Check if user supplied a local git dir and working tree
+        if self.git_work_tree.is_none() && self.git_dir.is_none() {
+            // None was provided
+            "".to_owned()
+        } else if self.git_dir.is_some() {
+            // user supplied both
+            format!(
+                "--work-tree={}",
+                self.git_work_tree.clone().unwrap().to_str().unwrap()
+            )
+        } else {
+            // user only supplied a working tree i.e. /home/user/mycode
+            let mut w = self.git_work_tree.clone().unwrap();
+            w.pop();
+            format!("--work-tree={}", w.to_str().unwrap())
+        }
+    }
+
+    fn get_git_dir(&self) -> String {
+        // Check if user supplied a local git dir and working tree
+        if self.git_dir.is_none() && self.git_work_tree.is_none() {
+            // None was provided
+            "".to_owned()
+        } else if self.git_work_tree.is_some() {
+            // user supplied both
+            format!(
+                "--git-dir={}",
+                self.git_dir.clone().unwrap().to_str().unwrap()
+            )
+        } else {
+            // user only supplied a git dir i.e. /home/user/mycode/.git
+            let mut g = self.git_dir.clone().unwrap();
+            g.push(".git");
+            format!("--git-dir={}", g.to_str().unwrap())
+        }
+    }
+
+    /// Retrieves the section title for a given alias
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let section = clog.section_for("feat");
+    /// assert_eq!(Some("Features"), section);
+    /// ```
+    pub fn section_for(&self, alias: &str) -> Option<&str> {
+        self.section_map
+            .iter()
+            .find(|&(_, v)| v.iter().any(|s| s == alias))
+            .map(|(k, _)| &**k)
+    }
+
+    /// Retrieves the full component name for a given alias (if one is defined)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    /// let component = clog.component_for("will_be_none");
+    /// assert_eq!(None, component);
     /// ```
     pub fn component_for(&self, alias: &str) -> Option<&String> {
         self.component_map
@@ -763,7 +1863,10 @@ impl Clog {
     /// ```
     pub fn write_changelog(&self) -> Result<()> {
         debug!("Writing changelog with preset options");
-        if let Some(ref cl) = self.outfile {
+        if !self.outputs.is_empty() {
+            debug!("{} outputs set", self.outputs.len());
+            self.for_each_output(|i, source| self.write_output(&self.outputs[i], source))
+        } else if let Some(ref cl) = self.outfile {
             debug!("outfile set to: {:?}", cl);
             self.write_changelog_to(cl)
         } else if let Some(ref cl) = self.infile {
@@ -771,23 +1874,15 @@ impl Clog {
             self.write_changelog_from(cl)
         } else {
             debug!("outfile and infile not set using stdout");
-            let out = stdout();
-            let mut out_buf = BufWriter::new(out.lock());
-            match self.out_format {
-                ChangelogFormat::Markdown => {
-                    let mut writer = MarkdownWriter::new(&mut out_buf);
-                    self.write_changelog_with(&mut writer)
-                }
-                ChangelogFormat::Json => {
-                    let mut writer = JsonWriter::new(&mut out_buf);
-                    self.write_changelog_with(&mut writer)
-                }
-            }
+            self.write_streamed(None, self.out_format, Source::Git)
         }
     }
 
     /// Writes the changelog to a specified file, and prepends new commits if
-    /// file exists, or creates the file if it doesn't.
+    /// file exists, or creates the file if it doesn't. If the file already
+    /// contains a release with the same version it is handled according to
+    /// `Clog::existing_release()`. Only Markdown changelogs are prepended to,
+    /// the other formats overwrite the file.
     ///
     /// # Example
     ///
@@ -800,36 +1895,12 @@ impl Clog {
     /// ```
     pub fn write_changelog_to<P: AsRef<Path>>(&self, cl: P) -> Result<()> {
         debug!("Writing changelog to file: {:?}", cl.as_ref());
-        let mut contents = String::with_capacity(256);
-        if let Some(ref infile) = self.infile {
-            debug!("infile set to: {:?}", infile);
-            File::open(infile)
-                .map(|mut f| f.read_to_string(&mut contents).ok())
-                .ok();
-        } else {
-            debug!("infile not set, trying the outfile");
-            File::open(cl.as_ref())
-                .map(|mut f| f.read_to_string(&mut contents).ok())
-                .ok();
-        }
-        contents.shrink_to_fit();
-
-        let mut file = File::create(cl.as_ref())?;
-        match self.out_format {
-            ChangelogFormat::Markdown => {
-                let mut writer = MarkdownWriter::new(&mut file);
-                self.write_changelog_with(&mut writer)?;
-            }
-            ChangelogFormat::Json => {
-                let mut writer = JsonWriter::new(&mut file);
-                self.write_changelog_with(&mut writer)?;
-            }
+        if !self.out_format.merges() {
+            return self.write_streamed(Some(cl.as_ref()), self.out_format, Source::Git);
         }
-        write!(&mut file, "\n\n\n")?;
-
-        file.write_all(contents.as_bytes())?;
+        let contents = self.render_to(cl.as_ref())?;
 
-        Ok(())
+        self.write_contents(Some(cl.as_ref()), &contents)
     }
 
     /// Writes the changelog from a specified input file, and appends new
@@ -846,48 +1917,117 @@ impl Clog {
     /// ```
     pub fn write_changelog_from<P: AsRef<Path>>(&self, cl: P) -> Result<()> {
         debug!("Writing changelog from file: {:?}", cl.as_ref());
-        let mut contents = String::with_capacity(256);
-        File::open(cl.as_ref())
-            .map(|mut f| f.read_to_string(&mut contents).ok())
-            .ok();
-        contents.shrink_to_fit();
-
-        if let Some(ref ofile) = self.outfile {
-            debug!("outfile set to: {:?}", ofile);
-            let mut file = File::create(ofile)?;
-            match self.out_format {
-                ChangelogFormat::Markdown => {
-                    let mut writer = MarkdownWriter::new(&mut file);
-                    self.write_changelog_with(&mut writer)?;
-                }
-                ChangelogFormat::Json => {
-                    let mut writer = JsonWriter::new(&mut file);
-                    self.write_changelog_with(&mut writer)?;
-                }
-            }
-            file.write_all(contents.as_bytes())?;
+        debug!("outfile set to: {:?}", self.outfile);
+        let outfile = self.outfile.as_deref().map(Path::new);
+        if !self.out_format.merges() {
+            return self.write_streamed(outfile, self.out_format, Source::Git);
+        }
+        let contents = self.render_from(cl.as_ref())?;
+
+        self.write_contents(outfile, &contents)
+    }
+
+    /// Computes what `Clog::write_changelog()` would write without writing
+    /// anything, returning the difference for every file (or `stdout`) it
+    /// would write to
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// for diff in clog.dry_run().unwrap() {
+    ///     print!("{}", diff.unified_diff());
+    /// }
+    /// ```
+    pub fn dry_run(&self) -> Result<Vec<ChangelogDiff>> {
+        debug!("Dry run with preset options");
+        if !self.outputs.is_empty() {
+            let mut diffs = self.outputs.iter().map(|_| None).collect::<Vec<_>>();
+            self.for_each_output(|i, source| {
+                let output = &self.outputs[i];
+                let new = self.render_output(output, source)?;
+                diffs[i] = Some(diff_against(output.outfile.as_deref().map(Path::new), new)?);
+                Ok(())
+            })?;
+            Ok(diffs.into_iter().flatten().collect())
+        } else if let Some(ref cl) = self.outfile {
+            self.dry_run_to(cl).map(|diff| vec![diff])
+        } else if let Some(ref cl) = self.infile {
+            let new = self.render_from(Path::new(cl))?;
+            Ok(vec![diff_against(None, new)?])
         } else {
-            debug!("outfile not set, using stdout");
-            let out = stdout();
-            let mut out_buf = BufWriter::new(out.lock());
-            {
-                match self.out_format {
-                    ChangelogFormat::Markdown => {
-                        let mut writer = MarkdownWriter::new(&mut out_buf);
-                        self.write_changelog_with(&mut writer)?;
-                    }
-                    ChangelogFormat::Json => {
-                        let mut writer = JsonWriter::new(&mut out_buf);
-                        self.write_changelog_with(&mut writer)?;
-                    }
-                }
-            }
-            write!(&mut out_buf, "\n\n\n")?;
+            let mut new = Vec::new();
+            self.write_format(self.out_format, Source::Git, &mut new)?;
+            Ok(vec![diff_against(
+                None,
+                String::from_utf8_lossy(&new).into_owned(),
+            )?])
+        }
+    }
 
-            out_buf.write_all(contents.as_bytes())?;
+    /// Computes what `Clog::write_changelog_to()` would write to a file
+    /// without writing it, returning the current and new contents of the file
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// let diff = clog.dry_run_to("changelog.md").unwrap();
+    /// print!("{}", diff.unified_diff());
+    /// if diff.would_change() {
+    ///     std::process::exit(1);
+    /// }
+    /// ```
+    pub fn dry_run_to<P: AsRef<Path>>(&self, cl: P) -> Result<ChangelogDiff> {
+        debug!("Dry run of writing changelog to file: {:?}", cl.as_ref());
+        let new = self.render_to(cl.as_ref())?;
+
+        diff_against(Some(cl.as_ref()), new)
+    }
+
+    /// Verifies that the changelog file set with `Clog::infile()` or
+    /// `Clog::outfile()` is up to date with the git history
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().changelog("changelog.md");
+    ///
+    /// assert!(clog.verify().unwrap().is_ok());
+    /// ```
+    pub fn verify(&self) -> Result<VerifyReport> {
+        match self.infile.as_ref().or(self.outfile.as_ref()) {
+            Some(cl) => self.verify_against(cl),
+            None => Err(Error::NoChangelog),
         }
+    }
 
-        Ok(())
+    /// Verifies that a Markdown changelog is up to date with the git history,
+    /// by regenerating the release for the configured range and comparing it
+    /// to the release of the same version in the changelog (or the newest
+    /// release if no version is set)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::Clog;
+    /// let clog = Clog::new().unwrap().version("1.2.0");
+    ///
+    /// let report = clog.verify_against("changelog.md").unwrap();
+    /// print!("{report}");
+    /// ```
+    pub fn verify_against<P: AsRef<Path>>(&self, cl: P) -> Result<VerifyReport> {
+        debug!("Verifying changelog: {:?}", cl.as_ref());
+        let mut contents = String::new();
+        File::open(cl.as_ref())?.read_to_string(&mut contents)?;
+        let sm = SectionMap::from_commits(self.get_commits()?);
+
+        Ok(verify::verify(self, &sm, &contents))
     }
 
     /// Writes a changelog with a specified `FormatWriter` format
@@ -915,4 +2055,257 @@ impl Clog {
 
         writer.write_changelog(self, &sm)
     }
+
+    /// Streams each commit to a specified `CommitWriter` as soon as it has
+    /// been parsed, without building a `SectionMap` first
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, fmt::NdjsonWriter};
+    /// # use std::io;
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Write one JSON object per commit to stdout
+    /// let out = io::stdout();
+    /// let mut out_buf = io::BufWriter::new(out.lock());
+    /// let mut writer = NdjsonWriter::new(&mut out_buf);
+    ///
+    /// clog.write_commits_with(&mut writer).unwrap();
+    /// ```
+    pub fn write_commits_with<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: CommitWriter,
+    {
+        debug!("Streaming commits to writer");
+        self.stream_commits(|commit| writer.write_commit(self, &commit))?;
+
+        writer.finish()
+    }
+
+    /// Collects the commits once and calls `f` with the index of each output
+    /// and the commits to write it from. NDJSON outputs are written from the
+    /// commits in the order of `git log` first, which are then moved into the
+    /// `SectionMap` the other outputs are written from, so no commit is copied.
+    /// The values of the release looked up with git, such as its date and
+    /// contributors, are resolved once for all of the other outputs, before
+    /// any of them changes the changelog `from_changelog` starts from.
+    fn for_each_output<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(usize, Source) -> Result<()>,
+    {
+        let (ndjson, others): (Vec<_>, Vec<_>) = (0..self.outputs.len())
+            .partition(|&i| self.outputs[i].format == ChangelogFormat::Ndjson);
+        let commits = self.get_commits()?;
+        for i in ndjson {
+            f(i, Source::Commits(&commits))?;
+        }
+        if others.is_empty() {
+            return Ok(());
+        }
+
+        let release = ReleaseInfo::resolve(self)?;
+        let sm = SectionMap::from_commits(commits);
+        others
+            .into_iter()
+            .try_for_each(|i| f(i, Source::Sections(&sm, &release)))
+    }
+
+    /// Writes a single output from commits which have already been collected
+    fn write_output(&self, output: &Output, source: Source) -> Result<()> {
+        debug!("Writing output: {:?}", output);
+        let outfile = output.outfile.as_deref().map(Path::new);
+        if !output.prepend || !output.format.merges() {
+            return self.write_streamed(outfile, output.format, source);
+        }
+        let contents = self.render_output(output, source)?;
+
+        self.write_contents(outfile, &contents)
+    }
+
+    /// Renders the complete contents `Clog::write_changelog_to()` writes to a
+    /// file
+    fn render_to(&self, cl: &Path) -> Result<String> {
+        let contents = if !self.out_format.merges() {
+            String::new()
+        } else if let Some(ref infile) = self.infile {
+            debug!("infile set to: {:?}", infile);
+            update::read_existing(Path::new(infile))?
+        } else {
+            debug!("infile not set, trying the outfile");
+            update::read_existing(cl)?
+        };
+
+        let mut new = Vec::new();
+        self.write_format(self.out_format, Source::Git, &mut new)?;
+        self.merge_release(&new, &contents)
+    }
+
+    /// Renders the complete contents `Clog::write_changelog_from()` writes
+    fn render_from(&self, cl: &Path) -> Result<String> {
+        let contents = if self.out_format.merges() {
+            update::read_existing(cl)?
+        } else {
+            String::new()
+        };
+
+        let mut new = Vec::new();
+        self.write_format(self.out_format, Source::Git, &mut new)?;
+        self.merge_release(&new, &contents)
+    }
+
+    /// Renders the complete contents of a single output from commits which
+    /// have already been collected
+    fn render_output(&self, output: &Output, source: Source) -> Result<String> {
+        let mut contents = String::new();
+        if output.prepend && output.format.merges() {
+            if let Some(infile) = output.infile.as_ref().or(output.outfile.as_ref()) {
+                debug!("reading old changelog data from: {:?}", infile);
+                contents = update::read_existing(Path::new(infile))?;
+            }
+        }
+
+        let mut new = Vec::new();
+        self.write_format(output.format, source, &mut new)?;
+        self.merge_release(&new, &contents)
+    }
+
+    /// Writes the complete contents of a changelog to a file, or `stdout` if
+    /// there is no file. Files are written to a temporary file first which
+    /// then atomically replaces the old file, so a failure never leaves a
+    /// partially written changelog behind.
+    fn write_contents(&self, outfile: Option<&Path>, contents: &str) -> Result<()> {
+        if let Some(outfile) = outfile {
+            debug!("Atomically writing changelog to: {:?}", outfile);
+            update::write_atomic(outfile, contents.as_bytes(), self.backup)
+        } else {
+            debug!("Writing changelog to stdout");
+            let out = stdout();
+            let mut out_buf = BufWriter::new(out.lock());
+            out_buf.write_all(contents.as_bytes())?;
+            out_buf.flush().map_err(Into::into)
+        }
+    }
+
+    /// Writes a changelog which isn't merged with old changelog data straight
+    /// to a file, or `stdout` if there is no file, as it is generated rather
+    /// than rendering it in memory first. Files are still replaced atomically.
+    fn write_streamed(
+        &self,
+        outfile: Option<&Path>,
+        format: ChangelogFormat,
+        source: Source,
+    ) -> Result<()> {
+        if let Some(outfile) = outfile {
+            debug!("Atomically streaming changelog to: {:?}", outfile);
+            update::write_atomic_with(outfile, self.backup, |out| {
+                self.write_format(format, source, out)
+            })
+        } else {
+            debug!("Streaming changelog to stdout");
+            let out = stdout();
+            let mut out_buf = BufWriter::new(out.lock());
+            self.write_format(format, source, &mut out_buf)?;
+            out_buf.flush().map_err(Into::into)
+        }
+    }
+
+    /// Combines a newly generated release with the old changelog contents,
+    /// only updating the region between markers if there are any, and handling
+    /// a release of the same version according to `existing_release`
+    fn merge_release(&self, new: &[u8], old: &str) -> Result<String> {
+        update::merge_release(self, &String::from_utf8_lossy(new), old)
+    }
+
+    /// Writes the changelog in a `ChangelogFormat`, from commits collected (or
+    /// streamed) from `git log` or which have already been collected
+    fn write_format<T: Write>(
+        &self,
+        format: ChangelogFormat,
+        source: Source,
+        out: &mut T,
+    ) -> Result<()> {
+        let release = match source {
+            Source::Sections(_, release) => Some(release),
+            Source::Git | Source::Commits(_) => None,
+        };
+        let mut writer: Box<dyn FormatWriter + '_> = match format {
+            ChangelogFormat::Csv => Box::new(CsvWriter::new(out)),
+            ChangelogFormat::Json => Box::new(JsonWriter::new(out).with_release(release)),
+            ChangelogFormat::Markdown => Box::new(MarkdownWriter::new(out).with_release(release)),
+            ChangelogFormat::Toml => Box::new(TomlWriter::new(out).with_release(release)),
+            ChangelogFormat::Yaml => Box::new(YamlWriter::new(out).with_release(release)),
+            ChangelogFormat::Ndjson => {
+                let mut writer = NdjsonWriter::new(out);
+                return match source {
+                    Source::Commits(commits) => {
+                        for commit in commits {
+                            writer.write_commit(self, commit)?;
+                        }
+                        writer.finish()
+                    }
+                    // Sections have lost the order of the commits
+                    Source::Git | Source::Sections(..) => self.write_commits_with(&mut writer),
+                };
+            }
+        };
+        match source {
+            Source::Git => {
+                writer.write_changelog(self, &SectionMap::from_commits(self.get_commits()?))
+            }
+            Source::Commits(commits) => {
+                writer.write_changelog(self, &SectionMap::from_commits(commits.to_vec()))
+            }
+            Source::Sections(sm, _) => writer.write_changelog(self, sm),
+        }
+    }
+}
+
+/// Where the commits a changelog is written from come from
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    /// Collected, or streamed by the formats which can, from `git log`
+    Git,
+    /// Already collected, in the order of `git log`
+    Commits(&'a [Commit]),
+    /// Already collected into sections, along with the values of the release
+    /// which were resolved once for every output
+    Sections(&'a SectionMap, &'a ReleaseInfo),
+}
+
+/// Pairs newly rendered contents with the current contents of the file they
+/// would be written to, or nothing for `stdout`
+fn diff_against(path: Option<&Path>, new: String) -> Result<ChangelogDiff> {
+    Ok(ChangelogDiff {
+        path: path.map(Path::to_path_buf),
+        old: path
+            .map(update::read_existing)
+            .transpose()?
+            .unwrap_or_default(),
+        new,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_contacts_without_email() {
+        let clog = Clog::default()
+            .git_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.git"))
+            .git_work_tree(env!("CARGO_MANIFEST_DIR"));
+
+        // i.e. a `Co-authored-by: Jane Doe` trailer
+        let contacts = HashSet::from(["Jane Doe".to_owned()]);
+        assert_eq!(clog.check_mailmap(contacts).unwrap(), vec![]);
+
+        let contacts = HashSet::from([
+            "Jane Doe".to_owned(),
+            "John Doe <john@example.com>".to_owned(),
+        ]);
+        let contributors = clog.check_mailmap(contacts).unwrap();
+        assert_eq!(contributors.len(), 1);
+        assert_eq!(contributors[0].email, "john@example.com");
+    }
 }
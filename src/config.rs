@@ -3,15 +3,28 @@ use std::{collections::HashMap, path::PathBuf};
 use indexmap::IndexMap;
 use serde::Deserialize;
 
-use crate::{fmt::ChangelogFormat, link_style::LinkStyle};
+use crate::{
+    fmt::{ChangelogFormat, CsvColumn},
+    link_style::{LinkStyle, LinkTemplates},
+    release_date::DateSource,
+    update::ExistingRelease,
+};
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct RawCfg {
     pub clog: RawClogCfg,
     #[serde(default)]
     pub sections: IndexMap<String, Vec<String>>,
     #[serde(default)]
     pub components: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub output: Vec<RawOutputCfg>,
+    pub links: Option<LinkTemplates>,
+    #[serde(default)]
+    pub issue_trackers: Vec<RawIssueTrackerCfg>,
+    #[serde(default)]
+    pub keywords: RawKeywordsCfg,
 }
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
@@ -24,8 +37,50 @@ pub struct RawClogCfg {
     pub outfile: Option<String>,
     pub git_dir: Option<PathBuf>,
     pub git_work_tree: Option<PathBuf>,
-    pub link_style: LinkStyle,
+    pub link_style: Option<LinkStyle>,
+    pub remote: Option<String>,
     pub output_format: ChangelogFormat,
+    pub csv_columns: Option<Vec<CsvColumn>>,
+    pub existing_release: ExistingRelease,
+    pub marker: Option<String>,
+    pub backup: bool,
+    pub embed_range: bool,
+    pub from_changelog: bool,
+    pub unreleased: bool,
+    pub autolink: bool,
+    pub contributors: bool,
+    pub highlight_first_time: bool,
+    pub bots: Option<Vec<String>>,
+    pub date_source: DateSource,
+    pub date: Option<String>,
+    pub date_format: Option<String>,
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RawOutputCfg {
+    #[serde(default)]
+    pub format: ChangelogFormat,
+    pub changelog: Option<String>,
+    pub infile: Option<String>,
+    pub outfile: Option<String>,
+    pub prepend: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawKeywordsCfg {
+    pub closes: Option<Vec<String>>,
+    pub refs: Option<Vec<String>>,
+    pub breaks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawIssueTrackerCfg {
+    pub pattern: String,
+    pub url: String,
 }
 
 #[cfg(test)]
@@ -44,14 +99,40 @@ mod tests {
             Some("https://github.com/clog-tool/clog-lib".into())
         );
         assert_eq!(cfg.clog.subtitle, Some("my awesome title".into()));
-        assert_eq!(cfg.clog.link_style, LinkStyle::Github);
+        assert_eq!(cfg.clog.link_style, Some(LinkStyle::Github));
+        assert_eq!(cfg.clog.remote, Some("upstream".into()));
         assert_eq!(cfg.clog.changelog, Some("mychangelog.md".into()));
         assert_eq!(cfg.clog.outfile, Some("MyChangelog.md".into()));
         assert_eq!(cfg.clog.infile, Some("My_old_changelog.md".into()));
         assert_eq!(cfg.clog.output_format, ChangelogFormat::Json);
+        assert_eq!(
+            cfg.clog.csv_columns,
+            Some(vec![
+                CsvColumn::Version,
+                CsvColumn::Subject,
+                CsvColumn::CommitLink
+            ])
+        );
         assert_eq!(cfg.clog.git_work_tree, Some("/myproject".into()));
         assert_eq!(cfg.clog.git_dir, Some("/myproject/.git".into()));
         assert!(cfg.clog.from_latest_tag);
+        assert_eq!(cfg.clog.existing_release, ExistingRelease::Error);
+        assert_eq!(cfg.clog.marker, Some("changelog".into()));
+        assert!(cfg.clog.backup);
+        assert!(cfg.clog.embed_range);
+        assert!(cfg.clog.from_changelog);
+        assert!(cfg.clog.unreleased);
+        assert!(cfg.clog.autolink);
+        assert!(cfg.clog.contributors);
+        assert!(cfg.clog.highlight_first_time);
+        assert_eq!(
+            cfg.clog.bots,
+            Some(vec!["*[bot]".into(), "renovate*".into()])
+        );
+        assert_eq!(cfg.clog.date_source, DateSource::Tag);
+        assert_eq!(cfg.clog.date, None);
+        assert_eq!(cfg.clog.date_format, Some("[day].[month].[year]".into()));
+        assert_eq!(cfg.clog.time_zone, Some("Europe/Berlin".into()));
         assert_eq!(
             cfg.sections.get("MySection"),
             Some(&vec!["mysec".into(), "ms".into()])
@@ -64,6 +145,28 @@ mod tests {
             cfg.components.get("MyLongComponentName"),
             Some(&vec!["long".into(), "comp".into()])
         );
+        assert_eq!(cfg.output.len(), 2);
+        assert_eq!(cfg.output[0].format, ChangelogFormat::Markdown);
+        assert_eq!(cfg.output[0].changelog, Some("CHANGELOG.md".into()));
+        assert_eq!(cfg.output[0].prepend, None);
+        assert_eq!(cfg.output[1].format, ChangelogFormat::Json);
+        assert_eq!(cfg.output[1].outfile, Some("changelog.json".into()));
+        assert_eq!(cfg.output[1].prepend, Some(false));
+        assert_eq!(
+            cfg.keywords.closes,
+            Some(vec!["closes".into(), "fixes".into(), "resolves".into()])
+        );
+        assert_eq!(
+            cfg.keywords.refs,
+            Some(vec!["refs".into(), "part of".into()])
+        );
+        assert_eq!(cfg.keywords.breaks, None);
+        assert_eq!(cfg.issue_trackers.len(), 1);
+        assert_eq!(cfg.issue_trackers[0].pattern, r"[A-Z]+-\d+");
+        assert_eq!(
+            cfg.issue_trackers[0].url,
+            "https://jira.example/browse/{id}"
+        );
     }
 
     #[test]
@@ -77,7 +180,38 @@ mod tests {
             cfg.clog.repository,
             Some("https://github.com/clog-tool/clog-lib".into())
         );
-        assert_eq!(cfg.clog.link_style, LinkStyle::Github);
+        assert_eq!(cfg.clog.link_style, None);
         assert!(cfg.clog.from_latest_tag);
     }
+
+    #[test]
+    fn link_templates() {
+        let cfg = "[clog]\nrepository = \"https://forge.example/r\"\n\n[links]\ncommit = \"{repo}/c/{hash}\"\n";
+        let cfg: RawCfg = toml::from_str(cfg).unwrap();
+        let links = cfg.links.clone().unwrap();
+        assert_eq!(links.commit, Some("{repo}/c/{hash}".into()));
+        assert_eq!(links.issue, None);
+
+        let clog = crate::Clog::try_from(cfg.clone()).unwrap();
+        assert_eq!(clog.link_templates, Some(links));
+        assert_eq!(
+            clog.commit_link("0123456789abcdef"),
+            "https://forge.example/r/c/0123456789abcdef"
+        );
+
+        let mut conflict = cfg.clone();
+        conflict.clog.link_style = Some(LinkStyle::Gitlab);
+        assert!(matches!(
+            crate::Clog::try_from(conflict),
+            Err(crate::error::Error::LinkConflict)
+        ));
+
+        let mut no_repo = cfg.clone();
+        no_repo.clog.repository = None;
+        no_repo.clog.remote = Some("clog-missing-remote".into());
+        assert!(crate::Clog::try_from(no_repo).is_err());
+
+        let cfg = "[clog]\n[links]\nissue = \"https://tracker.example/{hash}\"\n";
+        assert!(crate::Clog::try_from(toml::from_str::<RawCfg>(cfg).unwrap()).is_err());
+    }
 }
@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+/// A contributor of a release, i.e. the author or a co-author (from a
+/// `Co-authored-by:` trailer) of one of its commits
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::Clog;
+/// let clog = Clog::new().unwrap().contributors(true);
+///
+/// for contributor in clog.get_contributors().unwrap() {
+///     println!("{} <{}>", contributor.name, contributor.email);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Contributor {
+    /// The name, after applying any `.mailmap`
+    pub name: String,
+    /// The e-mail address, after applying any `.mailmap`
+    pub email: String,
+    /// Whether this is the first release the contributor contributed to,
+    /// which is only determined when `Clog::highlight_first_time` is set
+    pub first_time: bool,
+}
+
+impl Contributor {
+    /// The identity contributors are told apart by, since the same person may
+    /// use different spellings of their name
+    pub(crate) fn key(&self) -> String {
+        if self.email.is_empty() {
+            self.name.to_lowercase()
+        } else {
+            self.email.to_lowercase()
+        }
+    }
+}
+
+/// The marker starting the author lines of `CONTACTS_FORMAT`
+pub(crate) const AUTHOR_MARKER: char = '\x1e';
+
+/// The `git log` format listing the author of each commit, followed by the
+/// values of its `Co-authored-by:` trailers
+pub(crate) const CONTACTS_FORMAT: &str =
+    "%x1e%aN <%aE>%n%(trailers:key=Co-authored-by,valueonly,unfold)";
+
+/// Parses a contact such as `Jane Doe <jane@example.com>`
+pub(crate) fn parse_contact(contact: &str) -> Option<Contributor> {
+    let contact = contact.trim();
+    let (name, rest) = contact.split_once('<')?;
+    let email = rest.strip_suffix('>')?;
+    let name = name.trim();
+    if name.is_empty() && email.is_empty() {
+        return None;
+    }
+
+    Some(Contributor {
+        name: name.to_owned(),
+        email: email.trim().to_owned(),
+        first_time: false,
+    })
+}
+
+/// Removes contributors matching any of the bot patterns and duplicates of
+/// the same identity, sorting the rest by name
+pub(crate) fn unique(contributors: Vec<Contributor>, bots: &[String]) -> Vec<Contributor> {
+    let mut seen = HashSet::new();
+    let mut unique = contributors
+        .into_iter()
+        .filter(|c| !is_bot(bots, c))
+        .filter(|c| seen.insert(c.key()))
+        .collect::<Vec<_>>();
+    unique.sort_by_key(|c| c.name.to_lowercase());
+    unique
+}
+
+/// Whether the name or e-mail of a contributor matches any of the patterns,
+/// where `*` matches any text and matching ignores case
+pub(crate) fn is_bot(patterns: &[String], contributor: &Contributor) -> bool {
+    patterns.iter().any(|p| {
+        glob_match(&p.to_lowercase(), &contributor.name.to_lowercase())
+            || glob_match(&p.to_lowercase(), &contributor.email.to_lowercase())
+    })
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // There is always at least one part, even for an empty pattern
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // The pattern has no `*`
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(name: &str, email: &str) -> Contributor {
+        Contributor {
+            name: name.to_owned(),
+            email: email.to_owned(),
+            first_time: false,
+        }
+    }
+
+    #[test]
+    fn parses_contacts() {
+        assert_eq!(
+            parse_contact(" Jane Doe <jane@example.com>"),
+            Some(contact("Jane Doe", "jane@example.com"))
+        );
+        assert_eq!(parse_contact("Jane Doe"), None);
+    }
+
+    #[test]
+    fn skips_bots_and_duplicates() {
+        let bots = vec!["*[bot]".to_owned(), "renovate*".to_owned()];
+        let contributors = vec![
+            contact("zed", "zed@example.com"),
+            contact(
+                "dependabot[bot]",
+                "49699333+dependabot[bot]@users.noreply.github.com",
+            ),
+            contact("Ann", "ann@example.com"),
+            contact("Renovate Bot", "bot@renovateapp.com"),
+            contact("Ann B.", "ANN@example.com"),
+        ];
+        assert_eq!(
+            unique(contributors, &bots),
+            [
+                contact("Ann", "ann@example.com"),
+                contact("zed", "zed@example.com")
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("*[bot]", "dependabot[bot]"));
+        assert!(glob_match("*bot*", "a-bot-b"));
+        assert!(glob_match("ci", "ci"));
+        assert!(!glob_match("ci", "circle"));
+        assert!(!glob_match("a*b*c", "acb"));
+    }
+}
@@ -0,0 +1,323 @@
+use std::{fmt, path::PathBuf};
+
+/// The number of unchanged lines shown around each change
+const CONTEXT: usize = 3;
+
+/// The most edits searched for, beyond which the old lines are all replaced
+/// by the new ones in a single change
+const MAX_EDITS: isize = 1000;
+
+/// The result of a dry run, holding the current contents of an output and the
+/// contents it would have after writing the changelog
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::Clog;
+/// let clog = Clog::new().unwrap();
+///
+/// let diff = clog.dry_run_to("changelog.md").unwrap();
+/// if diff.would_change() {
+///     print!("{diff}");
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangelogDiff {
+    /// The file which would be written, or `None` for `stdout`
+    pub path: Option<PathBuf>,
+    /// The current contents
+    pub old: String,
+    /// The contents after writing the changelog
+    pub new: String,
+}
+
+impl ChangelogDiff {
+    /// Whether writing the changelog would change the contents of the output
+    pub fn would_change(&self) -> bool { self.old != self.new }
+
+    /// Renders a unified diff from the current to the new contents, which is
+    /// empty if nothing would change
+    pub fn unified_diff(&self) -> String {
+        let name = self
+            .path
+            .as_ref()
+            .map_or_else(|| "stdout".to_owned(), |p| p.display().to_string());
+        unified_diff(
+            &self.old,
+            &self.new,
+            &format!("a/{}", name.trim_start_matches('/')),
+            &format!("b/{}", name.trim_start_matches('/')),
+        )
+    }
+}
+
+impl fmt::Display for ChangelogDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.unified_diff()) }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Renders a unified diff of two texts, which is empty if they are equal
+pub(crate) fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    // Lines keep their newline, so a change to only the final newline is
+    // still a change
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_ops(&old, &new);
+
+    let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != Op::Equal)
+        .map(|(i, _)| i)
+        .collect();
+
+    // Group changes which are close enough to share their context into hunks
+    let mut i = 0;
+    while i < changes.len() {
+        let start = changes[i].saturating_sub(CONTEXT);
+        let mut last = changes[i];
+        while i + 1 < changes.len() && changes[i + 1] - last <= CONTEXT * 2 {
+            i += 1;
+            last = changes[i];
+        }
+        let end = (last + CONTEXT + 1).min(ops.len());
+        i += 1;
+
+        // Line numbers at the start of the hunk
+        let old_start = ops[..start].iter().filter(|op| **op != Op::Insert).count();
+        let new_start = ops[..start].iter().filter(|op| **op != Op::Delete).count();
+        let (mut o, mut n) = (old_start, new_start);
+        let mut body = String::new();
+        for op in &ops[start..end] {
+            match op {
+                Op::Equal => {
+                    push_line(&mut body, ' ', old[o]);
+                    o += 1;
+                    n += 1;
+                }
+                Op::Delete => {
+                    push_line(&mut body, '-', old[o]);
+                    o += 1;
+                }
+                Op::Insert => {
+                    push_line(&mut body, '+', new[n]);
+                    n += 1;
+                }
+            }
+        }
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n{body}",
+            range(old_start, o - old_start),
+            range(new_start, n - new_start)
+        ));
+    }
+
+    out
+}
+
+/// Adds a line to the body of a hunk, marking a last line without a newline
+/// the way `diff` does
+fn push_line(body: &mut String, prefix: char, line: &str) {
+    body.push(prefix);
+    body.push_str(line);
+    if !line.ends_with('\n') {
+        body.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Formats the range of a hunk, where empty ranges refer to the line before
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Computes the edits turning `old` into `new` with Myers' algorithm, after
+/// skipping any common prefix and suffix. This takes O((n + m) * d) time and
+/// O(d * d) memory for d edits, which stays small when a release is added to a
+/// long changelog. Texts needing more than `MAX_EDITS` edits are diffed as
+/// deleting all old lines and inserting all new ones.
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    let (n, m) = (a.len() as isize, b.len() as isize);
+
+    // v[k] is the furthest x reached on diagonal k = x - y, and trace keeps the
+    // diagonals -d..=d of v after each number of edits d for backtracking
+    let max = n + m;
+    let mut v = vec![0isize; 2 * max as usize + 2];
+    let at = |k: isize| (k + max) as usize;
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=max {
+        if d > MAX_EDITS {
+            let mut ops = vec![Op::Equal; prefix];
+            ops.extend(std::iter::repeat(Op::Delete).take(a.len()));
+            ops.extend(std::iter::repeat(Op::Insert).take(b.len()));
+            ops.extend(std::iter::repeat(Op::Equal).take(suffix));
+            return ops;
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                trace.push(v[at(-d)..=at(d)].to_vec());
+                break 'search;
+            }
+        }
+        trace.push(v[at(-d)..=at(d)].to_vec());
+    }
+
+    // Walk back from the end, following the edit taken at each d
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    ops.extend(std::iter::repeat(Op::Equal).take(suffix));
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let prev = &trace[d as usize - 1];
+        let get = |k: isize| prev[(k + d - 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        ops.push(if x == prev_x { Op::Insert } else { Op::Delete });
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.extend(std::iter::repeat(Op::Equal).take(x as usize + prefix));
+    ops.reverse();
+
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_changes() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+
+    #[test]
+    fn prepended_release() {
+        let diff = unified_diff(
+            "one\ntwo\nthree\nfour\nfive\n",
+            "new\n\none\ntwo\nthree\nfour\nfive\n",
+            "a/x",
+            "b/x",
+        );
+        assert_eq!(
+            diff,
+            "--- a/x\n+++ b/x\n@@ -1,3 +1,5 @@\n+new\n+\n one\n two\n three\n"
+        );
+    }
+
+    #[test]
+    fn separate_hunks() {
+        let old = (1..=20).map(|i| format!("{i}\n")).collect::<String>();
+        let new = (1..=20)
+            .filter(|&i| i != 19)
+            .map(|i| {
+                if i == 2 {
+                    "two\n".to_owned()
+                } else {
+                    format!("{i}\n")
+                }
+            })
+            .collect::<String>();
+        let diff = unified_diff(&old, &new, "a", "b");
+        assert_eq!(
+            diff,
+            concat!(
+                "--- a\n+++ b\n",
+                "@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n",
+                "@@ -16,5 +16,4 @@\n 16\n 17\n 18\n-19\n 20\n",
+            )
+        );
+    }
+
+    #[test]
+    fn from_empty() {
+        let diff = ChangelogDiff {
+            path: None,
+            old: String::new(),
+            new: "a\nb\n".into(),
+        };
+        assert!(diff.would_change());
+        assert_eq!(
+            diff.to_string(),
+            "--- a/stdout\n+++ b/stdout\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let diff = ChangelogDiff {
+            path: None,
+            old: "a\nb\n".into(),
+            new: "a\nb".into(),
+        };
+        assert!(diff.would_change());
+        assert_eq!(
+            diff.to_string(),
+            concat!(
+                "--- a/stdout\n+++ b/stdout\n",
+                "@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n",
+            )
+        );
+    }
+
+    #[test]
+    fn large_inputs() {
+        let old = (0..100_000).map(|i| format!("{i}\n")).collect::<String>();
+        let new = format!("new\n{}", old.replace("\n50000\n", "\nfifty\n"));
+        let diff = unified_diff(&old, &new, "a", "b");
+        assert_eq!(diff.lines().filter(|l| l.starts_with("@@")).count(), 2);
+        assert!(diff.contains("-50000\n+fifty\n"));
+
+        // Too many edits to search for become a single change
+        let old = (0..5_000).map(|i| format!("{i}\n")).collect::<String>();
+        let new = (0..5_000)
+            .map(|i| format!("{}\n", i * 2))
+            .collect::<String>();
+        let diff = unified_diff(&old, &new, "a", "b");
+        assert!(diff.starts_with("--- a\n+++ b\n@@ -1,5000 +1,5000 @@\n 0\n-1\n"));
+        assert_eq!(diff.lines().filter(|l| l.starts_with(' ')).count(), 1);
+    }
+}
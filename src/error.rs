@@ -17,12 +17,24 @@ pub enum Error {
     #[error("incorrect format for config file: {0}")]
     ConfigFormat(PathBuf),
 
+    #[error("{0}")]
+    Git(String),
+
     #[error("cannot get current directory")]
     CurrentDir,
 
     #[error("unrecognized link-style field")]
     LinkStyle,
 
+    #[error("link-style can't be combined with a [links] table")]
+    LinkConflict,
+
+    #[error("invalid link template `{template}`: {reason}")]
+    LinkTemplate { template: String, reason: String },
+
+    #[error("invalid issue tracker pattern `{pattern}`: {reason}")]
+    IssuePattern { pattern: String, reason: String },
+
     #[error("fatal I/O error with output file")]
     Io(#[from] std::io::Error),
 
@@ -32,12 +44,27 @@ pub enum Error {
     #[error("failed to convert date/time to string format")]
     Time(#[from] time::Error),
 
+    #[error("no changelog file was set to verify against")]
+    NoChangelog,
+
+    #[error("invalid date or time zone: {0}")]
+    InvalidDate(String),
+
+    #[error("release {0} already exists in the changelog")]
+    ReleaseExists(String),
+
     #[error("failed to convert {0} to valid ChangelogFormat")]
     ChangelogFormat(String),
 
     #[error("Failed to parse TOML configuration file")]
     Toml(#[from] toml::de::Error),
 
+    #[error("failed to write JSON changelog")]
+    Json(#[from] serde_json::Error),
+
+    #[error("failed to write TOML changelog")]
+    TomlSer(#[from] toml::ser::Error),
+
     #[error("unknown fatal error")]
     Unknown,
 }
@@ -1,19 +1,47 @@
+mod ansi_writer;
+mod csv_writer;
 mod json_writer;
+mod md_reader;
 mod md_writer;
+mod model;
+mod ndjson_writer;
+mod toml_writer;
+mod yaml_writer;
 
 use std::{result::Result as StdResult, str::FromStr};
 
 use strum::{Display, EnumString};
 
-pub use self::{json_writer::JsonWriter, md_writer::MarkdownWriter};
-use crate::{clog::Clog, error::Result, sectionmap::SectionMap};
+pub use self::{
+    ansi_writer::AnsiWriter,
+    csv_writer::{CsvColumn, CsvWriter},
+    json_writer::JsonWriter,
+    md_reader::{MarkdownReader, Release},
+    md_writer::MarkdownWriter,
+    ndjson_writer::NdjsonWriter,
+    toml_writer::TomlWriter,
+    yaml_writer::YamlWriter,
+};
+pub(crate) use self::{md_reader::parse_releases, model::ReleaseInfo};
+use crate::{clog::Clog, error::Result, git::Commit, sectionmap::SectionMap};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default, EnumString, Display)]
 #[strum(ascii_case_insensitive)]
 pub enum ChangelogFormat {
+    Csv,
     Json,
     #[default]
     Markdown,
+    Ndjson,
+    Toml,
+    Yaml,
+}
+
+impl ChangelogFormat {
+    /// Whether new changelog data in this format is merged with the old
+    /// changelog data. Only Markdown releases can be joined into one file, the
+    /// structured formats' documents always replace the old data.
+    pub(crate) fn merges(self) -> bool { self == ChangelogFormat::Markdown }
 }
 
 impl<'de> serde::de::Deserialize<'de> for ChangelogFormat {
@@ -31,11 +59,27 @@ impl<'de> serde::de::Deserialize<'de> for ChangelogFormat {
 /// `write_changelog()` accepts a `clog::SectionMap` which can be thought of
 /// similiar to a `clog` "AST" of sorts.
 ///
-/// `clog` provides two default implementors of this traint,
-/// `clog::fmt::MarkdownWriter` and `clog::fmt::JsonWriter` for writing Markdown
-/// and JSON respectively
+/// `clog` provides several default implementors of this traint, such as
+/// `clog::fmt::MarkdownWriter`, `clog::fmt::JsonWriter`,
+/// `clog::fmt::YamlWriter`, `clog::fmt::TomlWriter`, `clog::fmt::CsvWriter`
+/// and `clog::fmt::AnsiWriter` for writing colored terminal output
 pub trait FormatWriter {
     /// Writes a changelog from a given `clog::SectionMap` which can be thought
     /// of as an "AST" of sorts
     fn write_changelog(&mut self, options: &Clog, section_map: &SectionMap) -> Result<()>;
 }
+
+/// A trait that allows streaming the results of a `clog` run one commit at a
+/// time as they are parsed, instead of collecting every commit into a
+/// `clog::SectionMap` first. This keeps memory usage flat for very large
+/// histories.
+///
+/// `clog` provides `clog::fmt::NdjsonWriter` as a default implementor of this
+/// trait for writing newline delimited JSON
+pub trait CommitWriter {
+    /// Writes a single commit as soon as it has been parsed
+    fn write_commit(&mut self, options: &Clog, commit: &Commit) -> Result<()>;
+
+    /// Called once after the last commit has been written
+    fn finish(&mut self) -> Result<()> { Ok(()) }
+}
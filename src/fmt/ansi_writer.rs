@@ -0,0 +1,380 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    io::{self, IsTerminal},
+};
+
+use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionmap::SectionMap};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+
+/// Wraps a `std::io::Write` object to write `clog` output with ANSI colors and
+/// OSC 8 hyperlinks, which is useful for previewing a changelog in a terminal
+/// before writing it to a file
+///
+/// Colors and hyperlinks are disabled when the wrapped object is not a
+/// terminal, or when the `NO_COLOR` environment variable is set. Control
+/// characters in the changelog text, such as escape sequences in commit
+/// subjects, are escaped rather than passed on to the terminal.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::io::stdout;
+/// # use clog::{Clog, fmt::AnsiWriter};
+/// let clog = Clog::new().unwrap();
+///
+/// // Create the AnsiWriter wrapping stdout
+/// let out = stdout();
+/// let mut out_lock = out.lock();
+/// let mut writer = AnsiWriter::new(&mut out_lock);
+///
+/// // Use the AnsiWriter to preview the changelog
+/// clog.write_changelog_with(&mut writer).unwrap();
+/// ```
+pub struct AnsiWriter<'a> {
+    out: &'a mut dyn io::Write,
+    color: bool,
+}
+
+impl<'a> AnsiWriter<'a> {
+    /// Creates a new instance of the `AnsiWriter` struct using a
+    /// `std::io::Write` object, enabling colors only if the object is a
+    /// terminal and `NO_COLOR` is not set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io::stdout;
+    /// # use clog::{Clog, fmt::AnsiWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Create an AnsiWriter to wrap stdout
+    /// let out = stdout();
+    /// let mut out_lock = out.lock();
+    /// let mut writer = AnsiWriter::new(&mut out_lock);
+    /// ```
+    pub fn new<T: io::Write + IsTerminal + 'a>(writer: &'a mut T) -> AnsiWriter<'a> {
+        let color = use_color(writer.is_terminal(), env::var_os("NO_COLOR"));
+        AnsiWriter::with_color(writer, color)
+    }
+
+    /// Creates a new instance of the `AnsiWriter` struct using a
+    /// `std::io::Write` object, explicitly enabling or disabling colors and
+    /// hyperlinks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{Clog, fmt::AnsiWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Render into a buffer with colors forced on
+    /// let mut buf = Vec::new();
+    /// let mut writer = AnsiWriter::with_color(&mut buf, true);
+    /// ```
+    pub fn with_color<T: io::Write + 'a>(writer: &'a mut T, color: bool) -> AnsiWriter<'a> {
+        AnsiWriter { out: writer, color }
+    }
+
+    /// Wraps some text in the given color codes, if colors are enabled
+    fn paint(&self, codes: &str, text: &str) -> String {
+        if self.color {
+            format!("{codes}{text}{RESET}")
+        } else {
+            text.to_owned()
+        }
+    }
+
+    /// Wraps some text in an OSC 8 hyperlink, if colors are enabled
+    fn link(&self, url: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b]8;;{}\x1b\\{text}\x1b]8;;\x1b\\", escape(url))
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn write_header(&mut self, options: &Clog) -> Result<()> {
+        let subtitle = options.subtitle.clone().unwrap_or_default();
+        let version = options.version.clone().unwrap_or_default();
+
+        let date = options.get_release_date()?;
+        let title = format!("{version} {subtitle}");
+        let mut title = self.paint(BOLD, &escape(title.trim()));
+        if let Some(link) = options.get_compare_link()? {
+            title = self.link(&link, &title);
+        }
+        writeln!(
+            self.out,
+            "{} {}",
+            title,
+            self.paint(DIM, &format!("({date})"))
+        )
+        .map_err(Into::into)
+    }
+
+    /// Formats a list of issues as hyperlinks
+    fn issues(&self, options: &Clog, issues: &[String]) -> String {
+        issues
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                self.link(
+                    &options.issue_link(s),
+                    &self.paint(BLUE, &escape(&options.issue_label(s))),
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Writes the contributors of the release, if `contributors` is set
+    fn write_contributors(&mut self, options: &Clog) -> Result<()> {
+        if !options.contributors {
+            return Ok(());
+        }
+        let contributors = options.get_contributors()?;
+        if contributors.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            self.out,
+            "\n{}\n",
+            self.paint(&format!("{BOLD}{YELLOW}"), "Contributors")
+        )?;
+        for contributor in contributors {
+            if contributor.first_time {
+                writeln!(
+                    self.out,
+                    "  • {} {}",
+                    self.paint(BOLD, &escape(&contributor.name)),
+                    self.paint(DIM, "(first contribution)")
+                )?;
+            } else {
+                writeln!(self.out, "  • {}", escape(&contributor.name))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a particular section of a changelog
+    fn write_section(
+        &mut self,
+        options: &Clog,
+        title: &str,
+        section: &BTreeMap<&String, &Vec<Commit>>,
+    ) -> Result<()> {
+        if section.is_empty() {
+            return Ok(());
+        }
+
+        let title_codes = if title == "Breaking Changes" {
+            format!("{BOLD}{RED}")
+        } else {
+            format!("{BOLD}{YELLOW}")
+        };
+        writeln!(self.out, "\n{}\n", self.paint(&title_codes, &escape(title)))?;
+
+        for (component, entries) in section.iter() {
+            let nested = (entries.len() > 1) && !component.is_empty();
+            let component_text = self.paint(CYAN, &format!("{}:", escape(component)));
+
+            let prefix = if nested {
+                writeln!(self.out, "  • {component_text}")?;
+                "    •".to_owned()
+            } else if !component.is_empty() {
+                format!("  • {component_text}")
+            } else {
+                "  •".to_owned()
+            };
+
+            for entry in entries.iter() {
+                let hash = self.link(
+                    &options.commit_link(&entry.hash),
+                    &self.paint(DIM, entry.hash.get(..8).unwrap_or(&entry.hash)),
+                );
+                write!(
+                    self.out,
+                    "{prefix} {} ({hash}",
+                    escape(entry.subject.trim())
+                )?;
+
+                if let Some(pr) = &entry.pr {
+                    let pr = self.link(
+                        &options.pr_link(pr),
+                        &self.paint(BLUE, &options.pr_label(pr)),
+                    );
+                    write!(self.out, ", {pr}")?;
+                }
+                let closes = self.issues(options, &entry.closes);
+                if !closes.is_empty() {
+                    write!(self.out, ", closes {closes}")?;
+                }
+                let refs = self.issues(options, &entry.refs);
+                if !refs.is_empty() {
+                    write!(self.out, ", refs {refs}")?;
+                }
+                let breaks = self.issues(options, &entry.breaks);
+                if !breaks.is_empty() {
+                    write!(self.out, ", breaks {breaks}")?;
+                }
+
+                writeln!(self.out, ")")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Escapes the control characters of some text, i.e. `ESC` becomes `\u{1b}`,
+/// so text from commits can't inject escape sequences into the terminal
+fn escape(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(char::is_control) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.chars()
+            .map(|c| {
+                if c.is_control() {
+                    c.escape_default().to_string()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Whether colors are enabled, which requires a terminal and the `NO_COLOR`
+/// environment variable to be unset or empty
+fn use_color(is_terminal: bool, no_color: Option<OsString>) -> bool {
+    is_terminal && no_color.map_or(true, |v| v.is_empty())
+}
+
+impl<'a> FormatWriter for AnsiWriter<'a> {
+    fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
+        self.write_header(options)?;
+
+        // Get the section names ordered from `options.section_map`
+        let s_it = options
+            .section_map
+            .keys()
+            .filter_map(|sec| sm.sections.get(sec).map(|secmap| (sec, secmap)));
+        for (sec, secmap) in s_it {
+            self.write_section(
+                options,
+                &sec[..],
+                &secmap.iter().collect::<BTreeMap<_, _>>(),
+            )?;
+        }
+        self.write_contributors(options)?;
+
+        self.out.flush().map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn write(clog: &Clog, color: bool) -> String {
+        let commit = Commit {
+            hash: "abcdef1234567890".to_owned(),
+            subject: "add preview".to_owned(),
+            closes: vec!["12".to_owned()],
+            commit_type: "Features".to_owned(),
+            ..Commit::default()
+        };
+        let short = Commit {
+            hash: "abc".to_owned(),
+            subject: "short hash".to_owned(),
+            component: "core".to_owned(),
+            commit_type: "Bug Fixes".to_owned(),
+            ..Commit::default()
+        };
+        let sm = SectionMap::from_commits(vec![commit, short]);
+
+        let mut buf = Vec::new();
+        AnsiWriter::with_color(&mut buf, color)
+            .write_changelog(clog, &sm)
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn clog() -> Clog {
+        Clog::default()
+            .version("1.0.0")
+            .repository("https://github.com/o/r")
+            .date(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap())
+    }
+
+    #[test]
+    fn writes_plain_text() {
+        assert_eq!(
+            write(&clog(), false),
+            concat!(
+                "1.0.0 (2023-11-14)\n",
+                "\nFeatures\n\n",
+                "  • add preview (abcdef12, closes #12)\n",
+                "\nBug Fixes\n\n",
+                "  • core: short hash (abc)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn writes_colors_and_links() {
+        let out = write(&clog(), true);
+        assert!(out.starts_with("\x1b[1m1.0.0\x1b[0m \x1b[2m(2023-11-14)\x1b[0m\n"));
+        assert!(out.contains(
+            "\x1b]8;;https://github.com/o/r/commit/abcdef1234567890\x1b\\\x1b[2mabcdef12\x1b[0m\x1b]8;;\x1b\\"
+        ));
+        assert!(out.contains(
+            "\x1b]8;;https://github.com/o/r/issues/12\x1b\\\x1b[34m#12\x1b[0m\x1b]8;;\x1b\\"
+        ));
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        let commit = Commit {
+            hash: "abcdef1234567890".to_owned(),
+            subject: "evil \x1b]0;pwned\x07 \x1b[2J".to_owned(),
+            component: "co\x1b[31mre".to_owned(),
+            commit_type: "Features".to_owned(),
+            ..Commit::default()
+        };
+        let sm = SectionMap::from_commits(vec![commit]);
+
+        let mut buf = Vec::new();
+        AnsiWriter::with_color(&mut buf, false)
+            .write_changelog(&clog(), &sm)
+            .unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        assert!(!out.contains('\x1b') && !out.contains('\x07'));
+        assert!(
+            out.contains("  • co\\u{1b}[31mre: evil \\u{1b}]0;pwned\\u{7} \\u{1b}[2J (abcdef12)\n")
+        );
+    }
+
+    #[test]
+    fn disables_colors() {
+        assert!(use_color(true, None));
+        assert!(use_color(true, Some(OsString::new())));
+        assert!(!use_color(true, Some("1".into())));
+        assert!(!use_color(false, None));
+    }
+}
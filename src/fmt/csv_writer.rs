@@ -0,0 +1,212 @@
+use std::{collections::HashSet, io, result::Result as StdResult, str::FromStr};
+
+use log::debug;
+use strum::{Display, EnumString};
+
+use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionmap::SectionMap};
+
+/// The columns which can be written by the `CsvWriter`. The order and
+/// selection of columns can be set with the `csv-columns` field of the
+/// configuration TOML file, or `Clog::csv_columns()`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, EnumString, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum CsvColumn {
+    Version,
+    Section,
+    Component,
+    Subject,
+    Author,
+    AuthorEmail,
+    Date,
+    ShortHash,
+    CommitLink,
+    Pr,
+    Closes,
+    Breaking,
+}
+
+impl CsvColumn {
+    /// All columns, in the default order
+    pub fn all() -> Vec<CsvColumn> {
+        vec![
+            CsvColumn::Version,
+            CsvColumn::Section,
+            CsvColumn::Component,
+            CsvColumn::Subject,
+            CsvColumn::Author,
+            CsvColumn::AuthorEmail,
+            CsvColumn::Date,
+            CsvColumn::ShortHash,
+            CsvColumn::CommitLink,
+            CsvColumn::Pr,
+            CsvColumn::Closes,
+            CsvColumn::Breaking,
+        ]
+    }
+}
+
+impl<'de> serde::de::Deserialize<'de> for CsvColumn {
+    fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Wraps a `std::io::Write` object to write `clog` output as CSV (RFC 4180),
+/// with one row per commit in the release
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use clog::{Clog, fmt::CsvWriter};
+/// let clog = Clog::new().unwrap();
+///
+/// // Create a file to hold our results, which the CsvWriter will wrap (note, .unwrap() is only
+/// // used to keep the example short and concise)
+/// let mut file = File::create("my_changelog.csv").ok().unwrap();
+///
+/// // Create the CSV Writer
+/// let mut writer = CsvWriter::new(&mut file);
+///
+/// // Use the CsvWriter to write the changelog
+/// clog.write_changelog_with(&mut writer).unwrap();
+/// ```
+pub struct CsvWriter<'a>(&'a mut dyn io::Write);
+
+impl<'a> CsvWriter<'a> {
+    /// Creates a new instance of the `CsvWriter` struct using a
+    /// `std::io::Write` object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io::{stdout, BufWriter};
+    /// # use clog::{Clog, fmt::CsvWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Create a CsvWriter to wrap stdout
+    /// let out = stdout();
+    /// let mut out_buf = BufWriter::new(out.lock());
+    /// let mut writer = CsvWriter::new(&mut out_buf);
+    /// ```
+    pub fn new<T: io::Write>(writer: &'a mut T) -> CsvWriter<'a> { CsvWriter(writer) }
+
+    /// Writes a single record, quoting fields as required by RFC 4180
+    fn write_record<S: AsRef<str>>(&mut self, fields: &[S]) -> Result<()> {
+        let record = fields
+            .iter()
+            .map(|f| quote(f.as_ref()))
+            .collect::<Vec<_>>()
+            .join(",");
+        write!(self.0, "{record}\r\n").map_err(Into::into)
+    }
+
+    /// Gets the value of a single column for a commit
+    fn field(options: &Clog, column: CsvColumn, section: &str, entry: &Commit) -> Result<String> {
+        let field = match column {
+            CsvColumn::Version => options.version.clone().unwrap_or_default(),
+            CsvColumn::Section => section.to_owned(),
+            CsvColumn::Component => entry.component.clone(),
+            CsvColumn::Subject => entry.subject.trim().to_owned(),
+            CsvColumn::Author => entry
+                .author
+                .as_ref()
+                .map(|a| a.name.clone())
+                .unwrap_or_default(),
+            CsvColumn::AuthorEmail => entry
+                .author
+                .as_ref()
+                .map(|a| a.email.clone())
+                .unwrap_or_default(),
+            CsvColumn::Date => match &entry.author {
+                Some(author) => options.format_date(author.date)?,
+                None => String::new(),
+            },
+            CsvColumn::ShortHash => entry.hash.chars().take(8).collect(),
+            CsvColumn::CommitLink => options.commit_link(&entry.hash),
+            CsvColumn::Pr => entry
+                .pr
+                .as_ref()
+                .map(|pr| options.pr_label(pr))
+                .unwrap_or_default(),
+            CsvColumn::Closes => entry
+                .closes
+                .iter()
+                .map(|s| options.issue_label(s))
+                .collect::<Vec<_>>()
+                .join(" "),
+            CsvColumn::Breaking => (!entry.breaks.is_empty()).to_string(),
+        };
+        Ok(field)
+    }
+}
+
+/// Quotes a field if it contains a comma, quote or line break, doubling any
+/// embedded quotes
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl<'a> FormatWriter for CsvWriter<'a> {
+    fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
+        debug!("Writing CSV changelog");
+        let header = options
+            .csv_columns
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        self.write_record(&header)?;
+
+        // Commits with breaking changes are listed in both their own section and
+        // "Breaking Changes", but should only produce a single row
+        let mut seen = HashSet::new();
+        let s_it = options
+            .section_map
+            .keys()
+            .filter_map(|sec| sm.sections.get(sec).map(|compmap| (sec, compmap)));
+        for (sec, compmap) in s_it {
+            for entries in compmap.values() {
+                for entry in entries.iter() {
+                    if !seen.insert(&entry.hash) {
+                        continue;
+                    }
+                    let record = options
+                        .csv_columns
+                        .iter()
+                        .map(|&col| CsvWriter::field(options, col, sec, entry))
+                        .collect::<Result<Vec<_>>>()?;
+                    self.write_record(&record)?;
+                }
+            }
+        }
+
+        self.0.flush().map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("plain subject"), "plain subject");
+        assert_eq!(quote("a, b"), "\"a, b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn column_names() {
+        assert_eq!(CsvColumn::ShortHash.to_string(), "short-hash");
+        assert_eq!("Commit-Link".parse(), Ok(CsvColumn::CommitLink));
+    }
+}
@@ -1,9 +1,18 @@
 use std::{collections::BTreeMap, io};
 
 use log::debug;
-use time::OffsetDateTime;
+use serde::Serialize;
 
-use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionmap::SectionMap};
+use crate::{
+    clog::Clog,
+    error::Result,
+    fmt::{
+        model::{Id, ReleaseInfo},
+        FormatWriter,
+    },
+    git::Commit,
+    sectionmap::SectionMap,
+};
 
 /// Wraps a `std::io::Write` object to write `clog` output in a JSON format
 ///
@@ -27,7 +36,7 @@ use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionma
 /// // Use the JsonWriter to write the changelog
 /// clog.write_changelog_with(&mut writer).unwrap();
 /// ```
-pub struct JsonWriter<'a>(&'a mut dyn io::Write);
+pub struct JsonWriter<'a>(&'a mut dyn io::Write, Option<&'a ReleaseInfo>);
 
 impl<'a> JsonWriter<'a> {
     /// Creates a new instance of the `JsonWriter` struct using a
@@ -45,24 +54,34 @@ impl<'a> JsonWriter<'a> {
     /// let mut out_buf = BufWriter::new(out.lock());
     /// let mut writer = JsonWriter::new(&mut out_buf);
     /// ```
-    pub fn new<T: io::Write>(writer: &'a mut T) -> JsonWriter<'a> { JsonWriter(writer) }
+    pub fn new<T: io::Write>(writer: &'a mut T) -> JsonWriter<'a> { JsonWriter(writer, None) }
+
+    /// Uses the release values already resolved for several outputs, instead
+    /// of resolving them when writing
+    pub(crate) fn with_release(mut self, release: Option<&'a ReleaseInfo>) -> Self {
+        self.1 = release;
+        self
+    }
 }
 
 impl<'a> JsonWriter<'a> {
     /// Writes the initial header inforamtion for a release
-    fn write_header(&mut self, options: &Clog) -> Result<()> {
+    fn write_header(&mut self, options: &Clog, release: &ReleaseInfo) -> Result<()> {
         write!(
             self.0,
-            "\"header\":{{\"version\":{:?},\"patch_version\":{:?},\"subtitle\":{},",
-            options.version,
+            "\"header\":{{\"version\":{},\"patch_version\":{:?},\"subtitle\":{},",
+            json(&options.version)?,
             options.patch_ver,
-            options.subtitle.as_deref().unwrap_or("null"),
+            json(&options.subtitle)?,
         )?;
 
-        let now = OffsetDateTime::now_utc();
-        // unwrap because the format description is static
-        let date = now.format(&time::format_description::parse("[year]-[month]-[day]").unwrap())?;
-        write!(self.0, "\"date\":\"{}\"}},", date).map_err(Into::into)
+        write!(
+            self.0,
+            "\"date\":{},\"compare_link\":{}}},",
+            json(&release.date)?,
+            json(&release.compare_link)?
+        )
+        .map_err(Into::into)
     }
 
     /// Writes a particular section of a changelog
@@ -87,60 +106,30 @@ impl<'a> JsonWriter<'a> {
                 if component.is_empty() {
                     write!(self.0, "null,")?;
                 } else {
-                    write!(self.0, "{:?},", component)?;
+                    write!(self.0, "{},", json(component)?)?;
                 }
                 write!(
                     self.0,
-                    "\"subject\":{:?},\"commit_link\":{:?},\"closes\":",
-                    entry.subject,
-                    options
-                        .link_style
-                        .commit_link(&*entry.hash, options.repo.as_deref())
+                    "\"subject\":{},\"commit_link\":{},\"pr\":",
+                    json(&entry.subject)?,
+                    json(&options.commit_link(&entry.hash))?
                 )?;
-
-                if entry.closes.is_empty() {
-                    write!(self.0, "null,")?;
-                } else {
-                    write!(self.0, "[")?;
-                    let mut c_it = entry.closes.iter().peekable();
-                    while let Some(issue) = c_it.next() {
-                        write!(
-                            self.0,
-                            "{{\"issue\":{},\"issue_link\":{:?}}}",
-                            issue,
-                            options.link_style.issue_link(issue, options.repo.as_ref())
-                        )?;
-                        if c_it.peek().is_some() {
-                            debug!("There are more close commits, adding comma");
-                            write!(self.0, ",")?;
-                        } else {
-                            debug!("There are no more close commits, no comma required");
-                        }
-                    }
-                    write!(self.0, "],")?;
-                }
-                write!(self.0, "\"breaks\":")?;
-                if entry.breaks.is_empty() {
-                    write!(self.0, "null}}")?;
-                } else {
-                    write!(self.0, "[")?;
-                    let mut c_it = entry.closes.iter().peekable();
-                    while let Some(issue) = c_it.next() {
-                        write!(
-                            self.0,
-                            "{{\"issue\":{},\"issue_link\":{:?}}}",
-                            issue,
-                            options.link_style.issue_link(issue, options.repo.as_ref())
-                        )?;
-                        if c_it.peek().is_some() {
-                            debug!("There are more breaks commits, adding comma");
-                            write!(self.0, ",")?;
-                        } else {
-                            debug!("There are no more breaks commits, no comma required");
-                        }
-                    }
-                    write!(self.0, "]}}")?;
+                match &entry.pr {
+                    Some(pr) => write!(
+                        self.0,
+                        "{},\"pr_link\":{},",
+                        json(&Id::new(pr))?,
+                        json(&options.pr_link(pr))?
+                    )?,
+                    None => write!(self.0, "null,\"pr_link\":null,")?,
                 }
+                write!(self.0, "\"closes\":")?;
+                self.write_issues(options, &entry.closes)?;
+                write!(self.0, ",\"refs\":")?;
+                self.write_issues(options, &entry.refs)?;
+                write!(self.0, ",\"breaks\":")?;
+                self.write_issues(options, &entry.breaks)?;
+                write!(self.0, "}}")?;
                 if e_it.peek().is_some() {
                     debug!("There are more commits, adding comma");
                     write!(self.0, ",")?;
@@ -158,16 +147,53 @@ impl<'a> JsonWriter<'a> {
         write!(self.0, "]").map_err(Into::into)
     }
 
+    /// Writes a list of issues and their links, or `null` if there are none
+    fn write_issues(&mut self, options: &Clog, issues: &[String]) -> Result<()> {
+        let mut i_it = issues.iter().filter(|s| !s.is_empty()).peekable();
+        if i_it.peek().is_none() {
+            write!(self.0, "null")?;
+            return Ok(());
+        }
+
+        write!(self.0, "[")?;
+        while let Some(issue) = i_it.next() {
+            self.write_issue(options, issue)?;
+            if i_it.peek().is_some() {
+                write!(self.0, ",")?;
+            }
+        }
+        write!(self.0, "]").map_err(Into::into)
+    }
+
+    /// Writes an issue and its link, keeping issue numbers as JSON numbers
+    /// while issue tracker keys are written as strings
+    fn write_issue(&mut self, options: &Clog, issue: &str) -> Result<()> {
+        write!(
+            self.0,
+            "{{\"issue\":{},\"issue_link\":{}}}",
+            json(&Id::new(issue))?,
+            json(&options.issue_link(issue))?
+        )
+        .map_err(Into::into)
+    }
+
     /// Writes some contents to the `Write` writer object
     #[allow(dead_code)]
     fn write(&mut self, content: &str) -> io::Result<()> { write!(self.0, "{}", content) }
 }
 
+/// Formats a value as JSON, i.e. a string with JSON escapes or `null` for
+/// `None`
+fn json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(Into::into)
+}
+
 impl<'a> FormatWriter for JsonWriter<'a> {
     fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
         debug!("Writing JSON changelog");
+        let release = ReleaseInfo::get(self.1, options)?;
         write!(self.0, "{{")?;
-        self.write_header(options)?;
+        self.write_header(options, &release)?;
 
         write!(self.0, "\"sections\":")?;
         let mut s_it = options
@@ -180,7 +206,7 @@ impl<'a> FormatWriter for JsonWriter<'a> {
             write!(self.0, "[")?;
             while let Some((sec, compmap)) = s_it.next() {
                 debug!("Writing section: {sec}");
-                write!(self.0, "{{\"title\":{sec:?},")?;
+                write!(self.0, "{{\"title\":{},", json(sec)?)?;
 
                 self.write_section(options, &compmap.iter().collect::<BTreeMap<_, _>>())?;
 
@@ -197,9 +223,73 @@ impl<'a> FormatWriter for JsonWriter<'a> {
             debug!("There are no sections to write");
             write!(self.0, "null")?;
         }
+        if options.contributors {
+            write!(self.0, ",\"contributors\":[")?;
+            let mut c_it = release.contributors.iter().peekable();
+            while let Some(c) = c_it.next() {
+                write!(
+                    self.0,
+                    "{{\"name\":{},\"email\":{},\"first_time\":{}}}",
+                    json(&c.name)?,
+                    json(&c.email)?,
+                    c.first_time
+                )?;
+                if c_it.peek().is_some() {
+                    write!(self.0, ",")?;
+                }
+            }
+            write!(self.0, "]")?;
+        }
 
         write!(self.0, "}}")?;
         debug!("Finished writing sections, flushing");
         self.0.flush().map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    #[test]
+    fn writes_valid_json() {
+        let clog = Clog::default()
+            .version("1.0.0")
+            .subtitle("the \"best\" release")
+            .repository("https://github.com/o/r")
+            .date(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap());
+        let commit = Commit {
+            hash: "abcdef1234567890".to_owned(),
+            subject: "escape \u{1b}[1m".to_owned(),
+            closes: vec!["1".to_owned(), "PROJ-2".to_owned()],
+            breaks: vec!["3".to_owned(), String::new()],
+            pr: Some("4".to_owned()),
+            commit_type: "Features".to_owned(),
+            ..Commit::default()
+        };
+        let sm = SectionMap::from_commits(vec![commit]);
+
+        let mut buf = Vec::new();
+        JsonWriter::new(&mut buf)
+            .write_changelog(&clog, &sm)
+            .unwrap();
+        let value: Value = serde_json::from_slice(&buf).unwrap();
+
+        assert_eq!(value["header"]["version"], "1.0.0");
+        assert_eq!(value["header"]["subtitle"], "the \"best\" release");
+        assert_eq!(value["header"]["compare_link"], Value::Null);
+        let entry = &value["sections"][0]["commits"][0];
+        assert_eq!(entry["subject"], "escape \u{1b}[1m");
+        assert_eq!(entry["pr"], 4);
+        assert_eq!(entry["closes"][0]["issue"], 1);
+        assert_eq!(entry["closes"][1]["issue"], "PROJ-2");
+        assert_eq!(entry["refs"], Value::Null);
+        assert_eq!(
+            entry["breaks"],
+            json!([{"issue": 3, "issue_link": "https://github.com/o/r/issues/3"}])
+        );
+    }
+}
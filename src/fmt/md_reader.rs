@@ -0,0 +1,361 @@
+use std::io;
+
+use indexmap::IndexMap;
+use regex::Regex;
+
+use crate::{
+    error::Result,
+    git::Commit,
+    sectionmap::{ComponentMap, SectionMap},
+    update::{anchor_version, heading_version},
+};
+
+/// The regexes parsing the entries of a changelog, which are compiled once per
+/// changelog rather than for every entry
+struct EntryRegexes {
+    entry: Regex,
+    refs: Regex,
+    pr: Regex,
+    link: Regex,
+    hash: Regex,
+}
+
+impl EntryRegexes {
+    fn new() -> EntryRegexes {
+        EntryRegexes {
+            entry: regex!(r"^(.*?) \(\[([^\]]+)\]\(([^)]*)\)(.*)\)$"),
+            refs: regex!(r"(closes|refs|breaks) ((?:\[[^\]]*\]\([^)]*\)(?:, )?)+)"),
+            pr: regex!(r"^, \[[#!](\d+)\]\([^)]*\)"),
+            link: regex!(r"\[([^\]]*)\]\(([^)]*)\)"),
+            hash: regex!(r"[0-9a-f]{40}"),
+        }
+    }
+}
+
+/// A single release read back from an existing changelog
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Release {
+    /// The version of the release (empty if none was set)
+    pub version: String,
+    /// The subtitle of the release (empty if none was set)
+    pub subtitle: String,
+    /// The date written in the release heading
+    pub date: Option<String>,
+    /// Whether this release used the smaller patch version heading
+    pub patch_ver: bool,
+    /// Maps the sections of the release, in the order they appear in the
+    /// changelog, to their components and commits
+    pub sections: IndexMap<String, ComponentMap>,
+}
+
+impl Release {
+    /// Creates a `clog::SectionMap` from this release, which can then be
+    /// written with any `clog::fmt::FormatWriter`
+    pub fn section_map(&self) -> SectionMap {
+        SectionMap {
+            sections: self
+                .sections
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+
+    /// Iterates over every commit of this release, in the order they appear in
+    /// the changelog
+    pub fn commits(&self) -> impl Iterator<Item = &Commit> {
+        self.sections
+            .values()
+            .flat_map(|compmap| compmap.values().flatten())
+    }
+}
+
+/// Wraps a `std::io::Read` object to read a changelog written by the
+/// `MarkdownWriter` back into its releases, sections, components and commits.
+///
+/// This allows converting existing changelogs to other formats, or comparing
+/// them against the git history.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::{fs::File, io};
+/// # use clog::{Clog, fmt::{FormatWriter, JsonWriter, MarkdownReader}};
+/// let clog = Clog::new().unwrap();
+///
+/// // Read the releases of an existing changelog (note, .unwrap() is only used to keep the
+/// // example short and concise)
+/// let mut file = File::open("changelog.md").unwrap();
+/// let releases = MarkdownReader::new(&mut file).read_changelog().unwrap();
+///
+/// // Write each release as JSON
+/// let out = io::stdout();
+/// let mut out_buf = io::BufWriter::new(out.lock());
+/// for release in releases {
+///     let options = clog.clone().version(release.version.clone());
+///     JsonWriter::new(&mut out_buf)
+///         .write_changelog(&options, &release.section_map())
+///         .unwrap();
+/// }
+/// ```
+pub struct MarkdownReader<'a>(&'a mut dyn io::Read);
+
+impl<'a> MarkdownReader<'a> {
+    /// Creates a new instance of the `MarkdownReader` struct using a
+    /// `std::io::Read` object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::fs::File;
+    /// # use clog::fmt::MarkdownReader;
+    /// let mut file = File::open("changelog.md").unwrap();
+    /// let mut reader = MarkdownReader::new(&mut file);
+    /// ```
+    pub fn new<T: io::Read>(reader: &'a mut T) -> MarkdownReader<'a> { MarkdownReader(reader) }
+
+    /// Reads every release in the changelog, newest first. Lines which were not
+    /// written by the `MarkdownWriter` are ignored.
+    pub fn read_changelog(&mut self) -> Result<Vec<Release>> {
+        let mut contents = String::new();
+        self.0.read_to_string(&mut contents)?;
+        Ok(parse_releases(&contents))
+    }
+}
+
+/// Parses every release from the contents of a Markdown changelog
+pub(crate) fn parse_releases(contents: &str) -> Vec<Release> {
+    let res = EntryRegexes::new();
+    let mut releases: Vec<Release> = vec![];
+    // Whether the last release was started by an anchor which hasn't been
+    // followed by its heading yet
+    let mut anchored = false;
+    let mut section = None;
+    let mut component = String::new();
+
+    for line in contents.lines() {
+        if let Some(version) = anchor_version(line) {
+            releases.push(Release {
+                version: version.to_owned(),
+                ..Release::default()
+            });
+            anchored = true;
+            section = None;
+            continue;
+        }
+        if heading_version(line).is_some() {
+            if !anchored {
+                releases.push(Release::default());
+            }
+            anchored = false;
+            section = None;
+            if let Some(release) = releases.last_mut() {
+                parse_heading(release, line);
+            }
+            continue;
+        }
+        anchored = false;
+
+        let Some(release) = releases.last_mut() else {
+            continue;
+        };
+        if let Some(title) = line.strip_prefix("#### ") {
+            let title = title.trim().to_owned();
+            release.sections.entry(title.clone()).or_default();
+            section = Some(title);
+            continue;
+        }
+        let Some(title) = section.as_ref() else {
+            continue;
+        };
+
+        let (comp, rest) = if let Some(rest) = line.strip_prefix("  * ") {
+            (component.clone(), rest)
+        } else if let Some(rest) = line.strip_prefix("* ") {
+            match rest.strip_prefix("**").and_then(|r| r.split_once(":**")) {
+                Some((comp, "")) => {
+                    // The heading of a component with several entries
+                    component = comp.to_owned();
+                    continue;
+                }
+                Some((comp, rest)) => (comp.to_owned(), rest),
+                None => (String::new(), rest),
+            }
+        } else {
+            continue;
+        };
+
+        if let Some(commit) = parse_entry(&res, title, &comp, rest.trim()) {
+            release
+                .sections
+                .entry(title.clone())
+                .or_default()
+                .entry(comp)
+                .or_default()
+                .push(commit);
+        }
+    }
+
+    releases
+}
+
+/// Fills in the version, subtitle and date of a release from its heading
+fn parse_heading(release: &mut Release, line: &str) {
+    release.patch_ver = line.starts_with("### ");
+    let mut rest = line.trim_start_matches('#').trim();
+
+    // Drop the link of a version linked to a compare view
+    let unlinked;
+    if let Some((text, link)) = rest.strip_prefix('[').and_then(|r| r.split_once("](")) {
+        if let Some(end) = link.find(')') {
+            unlinked = format!("{text}{}", &link[end + 1..]);
+            rest = &unlinked;
+        }
+    }
+
+    if let Some(open) = rest.rfind(" (").filter(|_| rest.ends_with(')')) {
+        release.date = Some(rest[open + 2..rest.len() - 1].to_owned());
+        rest = rest[..open].trim_end();
+    } else if rest.starts_with('(') && rest.ends_with(')') {
+        release.date = Some(rest[1..rest.len() - 1].to_owned());
+        rest = "";
+    }
+
+    if release.version.is_empty() {
+        let (version, subtitle) = rest.split_once(' ').unwrap_or((rest, ""));
+        release.version = version.to_owned();
+        rest = subtitle;
+    } else if rest
+        .get(..release.version.len())
+        .is_some_and(|v| v.eq_ignore_ascii_case(&release.version))
+    {
+        // The anchor of an Unreleased block is lowercase
+        rest = &rest[release.version.len()..];
+    }
+    release.subtitle = rest.trim().to_owned();
+}
+
+/// Parses a single changelog entry, i.e. `subject ([hash](link), [#3](link),
+/// closes [#1](link), refs [#2](link))`
+fn parse_entry(res: &EntryRegexes, section: &str, component: &str, line: &str) -> Option<Commit> {
+    let caps = res.entry.captures(line)?;
+    let short_hash = caps.get(2)?.as_str();
+    let hash = res
+        .hash
+        .find(caps.get(3)?.as_str())
+        .map_or(short_hash, |m| m.as_str());
+
+    let pr = res
+        .pr
+        .captures(caps.get(4)?.as_str())
+        .map(|pr| pr[1].to_owned());
+    let mut closes = vec![];
+    let mut refs = vec![];
+    let mut breaks = vec![];
+    for list in res.refs.captures_iter(caps.get(4)?.as_str()) {
+        let issues = res
+            .link
+            .captures_iter(&list[2])
+            .map(|link| link[1].trim_start_matches('#').to_owned())
+            .collect::<Vec<_>>();
+        match &list[1] {
+            "closes" => closes.extend(issues),
+            "refs" => refs.extend(issues),
+            _ => breaks.extend(issues),
+        }
+    }
+
+    Some(Commit {
+        hash: hash.to_owned(),
+        subject: caps.get(1)?.as_str().trim().to_owned(),
+        component: component.to_owned(),
+        closes,
+        refs,
+        breaks,
+        pr,
+        commit_type: section.to_owned(),
+        ..Commit::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = concat!(
+        "<a name=\"1.1.0\"></a>\n",
+        "## [1.1.0](https://github.com/o/r/compare/v1.0.1...v1.1.0) Crazy Dog (2024-02-01)\n\n\n",
+        "#### Features\n\n",
+        "* **api:**\n",
+        "  * one ([0123abcd](https://github.com/o/r/commit/0123abcd0123abcd0123abcd0123abcd0123abcd))\n",
+        "  * two ([4567abcd](https://github.com/o/r/commit/4567abcd4567abcd4567abcd4567abcd4567abcd), [#5](https://github.com/o/r/pull/5), closes [#1](https://github.com/o/r/issues/1), [#2](https://github.com/o/r/issues/2), refs [o/r2#4](https://github.com/o/r2/issues/4))\n",
+        "* **ui:**  three (with parens) ([89abcdef](89abcdef))\n\n",
+        "#### Bug Fixes\n\n",
+        "*   four ([fedcba98](https://github.com/o/r/commit/fedcba98fedcba98fedcba98fedcba98fedcba98), breaks [#3](https://github.com/o/r/issues/3))\n",
+        "\n\n\n",
+        "<a name=\"1.0.1\"></a>\n",
+        "### 1.0.1  (2024-01-01)\n\n\n",
+        "#### Features\n\n",
+        "*   five ([76543210](76543210))\n",
+    );
+
+    #[test]
+    fn reads_releases() {
+        let releases = parse_releases(CHANGELOG);
+        assert_eq!(releases.len(), 2);
+
+        let release = &releases[0];
+        assert_eq!(release.version, "1.1.0");
+        assert_eq!(release.subtitle, "Crazy Dog");
+        assert_eq!(release.date.as_deref(), Some("2024-02-01"));
+        assert!(!release.patch_ver);
+        assert_eq!(
+            release.sections.keys().collect::<Vec<_>>(),
+            ["Features", "Bug Fixes"]
+        );
+
+        let api = &release.sections["Features"]["api"];
+        assert_eq!(api.len(), 2);
+        assert_eq!(api[0].subject, "one");
+        assert_eq!(api[0].hash, "0123abcd0123abcd0123abcd0123abcd0123abcd");
+        assert_eq!(api[1].closes, ["1", "2"]);
+        assert_eq!(api[1].refs, ["o/r2#4"]);
+        assert_eq!(api[1].pr.as_deref(), Some("5"));
+        assert_eq!(api[0].pr, None);
+
+        let ui = &release.sections["Features"]["ui"];
+        assert_eq!(ui[0].subject, "three (with parens)");
+        assert_eq!(ui[0].hash, "89abcdef");
+
+        let fixes = &release.sections["Bug Fixes"][""];
+        assert_eq!(fixes[0].subject, "four");
+        assert_eq!(fixes[0].breaks, ["3"]);
+        assert_eq!(fixes[0].commit_type, "Bug Fixes");
+
+        let release = &releases[1];
+        assert_eq!(release.version, "1.0.1");
+        assert_eq!(release.subtitle, "");
+        assert!(release.patch_ver);
+        assert_eq!(release.commits().count(), 1);
+    }
+
+    #[test]
+    fn reads_heading_without_anchor() {
+        let releases = parse_releases(
+            "## 2.0.0 (2024-03-01)\n\n#### Features\n\n*   x ([1234abcd](1234abcd))\n",
+        );
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].version, "2.0.0");
+        assert_eq!(releases[0].date.as_deref(), Some("2024-03-01"));
+        assert_eq!(releases[0].commits().count(), 1);
+    }
+
+    #[test]
+    fn reads_unreleased_block() {
+        let releases =
+            parse_releases("<a name=\"unreleased\"></a>\n## Unreleased \n\n#### Features\n\n");
+        assert_eq!(releases[0].version, "unreleased");
+        assert_eq!(releases[0].subtitle, "");
+        assert_eq!(releases[0].date, None);
+    }
+}
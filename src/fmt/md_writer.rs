@@ -1,8 +1,14 @@
-use std::{collections::BTreeMap, io};
-
-use time::OffsetDateTime;
-
-use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionmap::SectionMap};
+use std::{borrow::Cow, collections::BTreeMap, io};
+
+use crate::{
+    autolink,
+    clog::Clog,
+    error::Result,
+    fmt::{model::ReleaseInfo, FormatWriter},
+    git::Commit,
+    sectionmap::SectionMap,
+    update::UNRELEASED,
+};
 
 /// Wraps a `std::io::Write` object to write `clog` output in a Markdown format
 ///
@@ -26,7 +32,7 @@ use crate::{clog::Clog, error::Result, fmt::FormatWriter, git::Commit, sectionma
 /// // Use the MarkdownWriter to write the changelog
 /// clog.write_changelog_with(&mut writer).unwrap();
 /// ```
-pub struct MarkdownWriter<'a>(&'a mut dyn io::Write);
+pub struct MarkdownWriter<'a>(&'a mut dyn io::Write, Option<&'a ReleaseInfo>);
 
 impl<'a> MarkdownWriter<'a> {
     /// Creates a new instance of the `MarkdownWriter` struct using a
@@ -45,27 +51,55 @@ impl<'a> MarkdownWriter<'a> {
     /// let mut writer = MarkdownWriter::new(&mut out_buf);
     /// ```
     pub fn new<T: io::Write + 'a>(writer: &'a mut T) -> MarkdownWriter<'a> {
-        MarkdownWriter(writer)
+        MarkdownWriter(writer, None)
+    }
+
+    /// Uses the release values already resolved for several outputs, instead
+    /// of resolving them when writing
+    pub(crate) fn with_release(mut self, release: Option<&'a ReleaseInfo>) -> Self {
+        self.1 = release;
+        self
     }
 
-    fn write_header(&mut self, options: &Clog) -> Result<()> {
+    fn write_header(&mut self, options: &Clog, release: &ReleaseInfo) -> Result<()> {
         let subtitle = options.subtitle.clone().unwrap_or_default();
         let version = options.version.clone().unwrap_or_default();
 
+        let linked = |text: &str| match release.compare_link.as_deref() {
+            Some(link) if !text.is_empty() => format!("[{text}]({link})"),
+            _ => text.to_owned(),
+        };
+
+        if options.unreleased && version.is_empty() {
+            writeln!(
+                self.0,
+                "<a name=\"{UNRELEASED}\"></a>\n## {} {subtitle}",
+                linked("Unreleased")
+            )?;
+            return self.write_range(release);
+        }
+
         let version_text = if options.patch_ver {
-            format!("### {version} {subtitle}")
+            format!("### {} {subtitle}", linked(&version))
         } else {
-            format!("## {version} {subtitle}")
+            format!("## {} {subtitle}", linked(&version))
         };
 
-        let now = OffsetDateTime::now_utc();
-        // unwrap because the format description is static
-        let date = now.format(&time::format_description::parse("[year]-[month]-[day]").unwrap())?;
         writeln!(
             self.0,
-            "<a name=\"{version}\"></a>\n{version_text} ({date})\n",
-        )
-        .map_err(Into::into)
+            "<a name=\"{version}\"></a>\n{version_text} ({})",
+            release.date
+        )?;
+        self.write_range(release)
+    }
+
+    /// Ends the header of a release, recording its range of commits if
+    /// `embed_range` is set
+    fn write_range(&mut self, release: &ReleaseInfo) -> Result<()> {
+        if let Some((from, to)) = &release.range {
+            writeln!(self.0, "<!-- clog: range={from}..{to} -->")?;
+        }
+        writeln!(self.0).map_err(Into::into)
     }
 
     /// Writes a particular section of a changelog
@@ -95,48 +129,38 @@ impl<'a> MarkdownWriter<'a> {
             };
 
             for entry in entries.iter() {
+                let subject = if options.autolink {
+                    Cow::Owned(autolink::link_subject(options, &entry.subject))
+                } else {
+                    Cow::Borrowed(&entry.subject)
+                };
                 write!(
                     self.0,
-                    "{prefix} {} ([{}]({})",
-                    entry.subject,
+                    "{prefix} {subject} ([{}]({})",
                     &entry.hash[0..8],
-                    options
-                        .link_style
-                        .commit_link(&*entry.hash, options.repo.as_deref())
+                    options.commit_link(&entry.hash)
                 )?;
 
-                if !entry.closes.is_empty() {
-                    let closes_string = entry
-                        .closes
-                        .iter()
-                        .map(|s| {
-                            format!(
-                                "[#{s}]({})",
-                                options.link_style.issue_link(s, options.repo.as_ref())
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join(", ");
-
-                    write!(self.0, ", closes {closes_string}")?;
+                if let Some(pr) = &entry.pr {
+                    write!(
+                        self.0,
+                        ", [{}]({})",
+                        options.pr_label(pr),
+                        options.pr_link(pr)
+                    )?;
+                }
+                let closes = issues(options, &entry.closes);
+                if !closes.is_empty() {
+                    write!(self.0, ", closes {closes}")?;
                 }
-                if !entry.breaks.is_empty() {
-                    let breaks_string = entry
-                        .breaks
-                        .iter()
-                        .map(|s| {
-                            format!(
-                                "[#{s}]({})",
-                                options.link_style.issue_link(s, options.repo.as_ref())
-                            )
-                        })
-                        .collect::<Vec<String>>()
-                        .join(", ");
-
-                    // 5 = "[#]()" i.e. a commit message that only said "BREAKING"
-                    if breaks_string.len() != 5 {
-                        write!(self.0, ", breaks {breaks_string}")?;
-                    }
+                let refs = issues(options, &entry.refs);
+                if !refs.is_empty() {
+                    write!(self.0, ", refs {refs}")?;
+                }
+                // Empty issues are commit messages that only said "BREAKING"
+                let breaks = issues(options, &entry.breaks);
+                if !breaks.is_empty() {
+                    write!(self.0, ", breaks {breaks}")?;
                 }
 
                 writeln!(self.0, ")")?;
@@ -146,6 +170,24 @@ impl<'a> MarkdownWriter<'a> {
         Ok(())
     }
 
+    /// Writes the contributors of the release, if `contributors` is set
+    fn write_contributors(&mut self, release: &ReleaseInfo) -> Result<()> {
+        if release.contributors.is_empty() {
+            return Ok(());
+        }
+
+        write!(self.0, "\n#### Contributors\n\n")?;
+        for contributor in &release.contributors {
+            if contributor.first_time {
+                writeln!(self.0, "* **{}** (first contribution)", contributor.name)?;
+            } else {
+                writeln!(self.0, "* {}", contributor.name)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Writes some contents to the `Write` writer object
     #[allow(dead_code)]
     fn write(&mut self, content: &str) -> Result<()> {
@@ -154,9 +196,20 @@ impl<'a> MarkdownWriter<'a> {
     }
 }
 
+/// Formats a list of issues as Markdown links
+fn issues(options: &Clog, issues: &[String]) -> String {
+    issues
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("[{}]({})", options.issue_label(s), options.issue_link(s)))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 impl<'a> FormatWriter for MarkdownWriter<'a> {
     fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
-        self.write_header(options)?;
+        let release = ReleaseInfo::get(self.1, options)?;
+        self.write_header(options, &release)?;
 
         // Get the section names ordered from `options.section_map`
         let s_it = options
@@ -170,6 +223,7 @@ impl<'a> FormatWriter for MarkdownWriter<'a> {
                 &secmap.iter().collect::<BTreeMap<_, _>>(),
             )?;
         }
+        self.write_contributors(&release)?;
 
         self.0.flush().map_err(Into::into)
     }
@@ -0,0 +1,186 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use serde::Serialize;
+
+use crate::{
+    clog::Clog, contributors::Contributor, error::Result, git::Commit, sectionmap::SectionMap,
+};
+
+/// The values of a release which are looked up with git rather than taken
+/// from its commits. They are resolved once per run, so several outputs of the
+/// same release don't each run git for them.
+#[derive(Debug, Clone)]
+pub(crate) struct ReleaseInfo {
+    pub date: String,
+    pub compare_link: Option<String>,
+    /// The full hashes of the range of commits, only resolved when
+    /// `embed_range` is set
+    pub range: Option<(String, String)>,
+    /// Only collected when `contributors` is set
+    pub contributors: Vec<Contributor>,
+}
+
+impl ReleaseInfo {
+    pub fn resolve(options: &Clog) -> Result<ReleaseInfo> {
+        Ok(ReleaseInfo {
+            date: options.get_release_date()?,
+            compare_link: options.get_compare_link()?,
+            range: if options.embed_range {
+                Some(options.get_range_hashes()?)
+            } else {
+                None
+            },
+            contributors: if options.contributors {
+                options.get_contributors()?
+            } else {
+                vec![]
+            },
+        })
+    }
+
+    /// Uses the values resolved for every output, or resolves them for a
+    /// writer used on its own
+    pub fn get<'r>(
+        release: Option<&'r ReleaseInfo>,
+        options: &Clog,
+    ) -> Result<Cow<'r, ReleaseInfo>> {
+        match release {
+            Some(release) => Ok(Cow::Borrowed(release)),
+            None => ReleaseInfo::resolve(options).map(Cow::Owned),
+        }
+    }
+}
+
+/// An owned representation of a release, with the same structure the
+/// `JsonWriter` produces, for writers backed by a serializer or which walk
+/// the changelog generically
+#[derive(Debug, Serialize)]
+pub(crate) struct Changelog {
+    pub header: Header,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<Section>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Header {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub patch_version: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_link: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Section {
+    pub title: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Entry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    pub subject: String,
+    pub commit_link: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr: Option<Id>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_link: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub closes: Vec<Issue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub refs: Vec<Issue>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub breaks: Vec<Issue>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Issue {
+    pub issue: Id,
+    pub issue_link: String,
+}
+
+/// An issue or pull request id, which is written as a number when it is one
+/// (i.e. `12`) and as a string otherwise (i.e. `PROJ-12` or `o/r#12`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum Id {
+    Number(u64),
+    Key(String),
+}
+
+impl Id {
+    pub fn new(id: &str) -> Id {
+        match id.parse::<u64>() {
+            // Leading zeros or a sign would be lost as a number
+            Ok(n) if n.to_string() == id => Id::Number(n),
+            _ => Id::Key(id.to_owned()),
+        }
+    }
+}
+
+impl Changelog {
+    /// Builds the release from a `clog::SectionMap`, ordering sections by
+    /// `options.section_map`
+    pub fn new(options: &Clog, release: &ReleaseInfo, sm: &SectionMap) -> Changelog {
+        let sections = options
+            .section_map
+            .keys()
+            .filter_map(|sec| sm.sections.get(sec).map(|compmap| (sec, compmap)))
+            .map(|(sec, compmap)| Section {
+                title: sec.clone(),
+                commits: compmap
+                    .iter()
+                    .collect::<BTreeMap<_, _>>()
+                    .into_iter()
+                    .flat_map(|(component, entries)| {
+                        entries.iter().map(|e| Entry::new(options, component, e))
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Changelog {
+            header: Header {
+                version: options.version.clone(),
+                patch_version: options.patch_ver,
+                subtitle: options.subtitle.clone(),
+                date: release.date.clone(),
+                compare_link: release.compare_link.clone(),
+            },
+            sections,
+            contributors: release.contributors.clone(),
+        }
+    }
+}
+
+impl Entry {
+    fn new(options: &Clog, component: &str, entry: &Commit) -> Entry {
+        let issues = |issues: &[String]| {
+            issues
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(|s| Issue {
+                    issue: Id::new(s),
+                    issue_link: options.issue_link(s),
+                })
+                .collect()
+        };
+        Entry {
+            component: (!component.is_empty()).then(|| component.to_owned()),
+            subject: entry.subject.trim().to_owned(),
+            commit_link: options.commit_link(&entry.hash),
+            pr: entry.pr.as_deref().map(Id::new),
+            pr_link: entry.pr.as_ref().map(|pr| options.pr_link(pr)),
+            closes: issues(&entry.closes),
+            refs: issues(&entry.refs),
+            breaks: issues(&entry.breaks),
+        }
+    }
+}
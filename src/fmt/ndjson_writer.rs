@@ -0,0 +1,161 @@
+use std::io;
+
+use log::debug;
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+
+use crate::{
+    clog::Clog,
+    error::Result,
+    fmt::{model::Id, CommitWriter},
+    git::{Commit, Signature},
+};
+
+/// A single line of NDJSON output
+#[derive(Serialize)]
+struct Record<'c> {
+    hash: &'c str,
+    #[serde(rename = "type")]
+    commit_type: &'c str,
+    section: &'c str,
+    component: Option<&'c str>,
+    subject: &'c str,
+    author: Option<Person<'c>>,
+    committer: Option<Person<'c>>,
+    pr: Option<Id>,
+    closes: Vec<Id>,
+    refs: Vec<Id>,
+    breaks: Vec<Id>,
+    breaking: bool,
+}
+
+/// The author or committer of a commit, with the date in RFC 3339 format
+#[derive(Serialize)]
+struct Person<'c> {
+    name: &'c str,
+    email: &'c str,
+    date: String,
+}
+
+impl<'c> Person<'c> {
+    fn new(signature: Option<&'c Signature>) -> Result<Option<Person<'c>>> {
+        let Some(sig) = signature else {
+            return Ok(None);
+        };
+        Ok(Some(Person {
+            name: &sig.name,
+            email: &sig.email,
+            date: sig.date.format(&Rfc3339)?,
+        }))
+    }
+}
+
+/// The issues of a commit, leaving out the empty ones of breaking changes
+/// without an issue, with the same ids as the other structured formats
+fn issues(issues: &[String]) -> Vec<Id> {
+    issues
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| Id::new(s))
+        .collect()
+}
+
+/// Wraps a `std::io::Write` object to stream `clog` output as newline delimited
+/// JSON, i.e. one JSON object per commit and line
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use clog::{Clog, fmt::NdjsonWriter};
+/// let clog = Clog::new().unwrap();
+///
+/// // Create a file to hold our results, which the NdjsonWriter will wrap (note, .unwrap() is only
+/// // used to keep the example short and concise)
+/// let mut file = File::create("commits.ndjson").ok().unwrap();
+///
+/// // Create the NDJSON Writer
+/// let mut writer = NdjsonWriter::new(&mut file);
+///
+/// // Use the NdjsonWriter to stream every commit as it is parsed
+/// clog.write_commits_with(&mut writer).unwrap();
+/// ```
+pub struct NdjsonWriter<'a>(&'a mut dyn io::Write);
+
+impl<'a> NdjsonWriter<'a> {
+    /// Creates a new instance of the `NdjsonWriter` struct using a
+    /// `std::io::Write` object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io::{stdout, BufWriter};
+    /// # use clog::{Clog, fmt::NdjsonWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Create a NdjsonWriter to wrap stdout
+    /// let out = stdout();
+    /// let mut out_buf = BufWriter::new(out.lock());
+    /// let mut writer = NdjsonWriter::new(&mut out_buf);
+    /// ```
+    pub fn new<T: io::Write>(writer: &'a mut T) -> NdjsonWriter<'a> { NdjsonWriter(writer) }
+}
+
+impl<'a> CommitWriter for NdjsonWriter<'a> {
+    fn write_commit(&mut self, _options: &Clog, commit: &Commit) -> Result<()> {
+        debug!("Writing commit: {}", commit.hash);
+        let record = Record {
+            hash: &commit.hash,
+            commit_type: &commit.raw_commit_type,
+            section: &commit.commit_type,
+            component: Some(&*commit.component).filter(|c| !c.is_empty()),
+            subject: commit.subject.trim(),
+            author: Person::new(commit.author.as_ref())?,
+            committer: Person::new(commit.committer.as_ref())?,
+            pr: commit.pr.as_deref().map(Id::new),
+            closes: issues(&commit.closes),
+            refs: issues(&commit.refs),
+            breaks: issues(&commit.breaks),
+            breaking: !commit.breaks.is_empty(),
+        };
+
+        serde_json::to_writer(&mut *self.0, &record)?;
+        writeln!(self.0).map_err(Into::into)
+    }
+
+    fn finish(&mut self) -> Result<()> { self.0.flush().map_err(Into::into) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_json() {
+        let commit = Commit {
+            hash: "abc".to_owned(),
+            subject: " colored \x1b[1m\"output\"\u{7f} ".to_owned(),
+            breaks: vec![String::new()],
+            closes: vec!["12".to_owned(), "PROJ-3".to_owned()],
+            pr: Some("4".to_owned()),
+            commit_type: "Features".to_owned(),
+            raw_commit_type: "feat".to_owned(),
+            ..Commit::default()
+        };
+        let mut buf = Vec::new();
+        NdjsonWriter::new(&mut buf)
+            .write_commit(&Clog::default(), &commit)
+            .unwrap();
+
+        let line = String::from_utf8(buf).unwrap();
+        assert!(line.ends_with("}\n") && line.lines().count() == 1);
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["subject"], "colored \x1b[1m\"output\"\u{7f}");
+        assert_eq!(value["type"], "feat");
+        assert_eq!(value["component"], serde_json::Value::Null);
+        assert_eq!(value["breaks"], serde_json::json!([]));
+        assert_eq!(value["closes"], serde_json::json!([12, "PROJ-3"]));
+        assert_eq!(value["pr"], 4);
+        assert_eq!(value["breaking"], true);
+    }
+}
@@ -0,0 +1,118 @@
+use std::io;
+
+use log::debug;
+
+use crate::{
+    clog::Clog,
+    error::Result,
+    fmt::{
+        model::{Changelog, ReleaseInfo},
+        FormatWriter,
+    },
+    sectionmap::SectionMap,
+};
+
+/// Wraps a `std::io::Write` object to write `clog` output in a TOML format,
+/// using the same structure as the `JsonWriter`. Since TOML has no `null`,
+/// empty values are omitted instead.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use clog::{Clog, fmt::TomlWriter};
+/// let clog = Clog::new().unwrap();
+///
+/// // Create a file to hold our results, which the TomlWriter will wrap (note, .unwrap() is only
+/// // used to keep the example short and concise)
+/// let mut file = File::create("my_changelog.toml").ok().unwrap();
+///
+/// // Create the TOML Writer
+/// let mut writer = TomlWriter::new(&mut file);
+///
+/// // Use the TomlWriter to write the changelog
+/// clog.write_changelog_with(&mut writer).unwrap();
+/// ```
+pub struct TomlWriter<'a>(&'a mut dyn io::Write, Option<&'a ReleaseInfo>);
+
+impl<'a> TomlWriter<'a> {
+    /// Creates a new instance of the `TomlWriter` struct using a
+    /// `std::io::Write` object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io::{stdout, BufWriter};
+    /// # use clog::{Clog, fmt::TomlWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Create a TomlWriter to wrap stdout
+    /// let out = stdout();
+    /// let mut out_buf = BufWriter::new(out.lock());
+    /// let mut writer = TomlWriter::new(&mut out_buf);
+    /// ```
+    pub fn new<T: io::Write>(writer: &'a mut T) -> TomlWriter<'a> { TomlWriter(writer, None) }
+
+    /// Uses the release values already resolved for several outputs, instead
+    /// of resolving them when writing
+    pub(crate) fn with_release(mut self, release: Option<&'a ReleaseInfo>) -> Self {
+        self.1 = release;
+        self
+    }
+}
+
+impl<'a> FormatWriter for TomlWriter<'a> {
+    fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
+        debug!("Writing TOML changelog");
+        let release = ReleaseInfo::get(self.1, options)?;
+        let changelog = Changelog::new(options, &release, sm);
+
+        write!(self.0, "{}", toml::to_string(&changelog)?)?;
+        self.0.flush().map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+    use toml::Value;
+
+    use super::*;
+    use crate::git::Commit;
+
+    #[test]
+    fn round_trips() {
+        let clog = Clog::default()
+            .version("1.0.0")
+            .repository("https://github.com/o/r")
+            .date(OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap());
+        let commit = Commit {
+            hash: "abcdef1234567890".to_owned(),
+            subject: "quote \"it\"".to_owned(),
+            component: "core".to_owned(),
+            closes: vec!["12".to_owned(), "PROJ-2".to_owned()],
+            pr: Some("482".to_owned()),
+            commit_type: "Features".to_owned(),
+            ..Commit::default()
+        };
+        let sm = SectionMap::from_commits(vec![commit]);
+
+        let mut buf = Vec::new();
+        TomlWriter::new(&mut buf)
+            .write_changelog(&clog, &sm)
+            .unwrap();
+        let value: Value = toml::from_str(std::str::from_utf8(&buf).unwrap()).unwrap();
+
+        assert_eq!(value["header"]["version"].as_str(), Some("1.0.0"));
+        assert_eq!(value["header"]["date"].as_str(), Some("2023-11-14"));
+        let section = &value["sections"][0];
+        assert_eq!(section["title"].as_str(), Some("Features"));
+        let entry = &section["commits"][0];
+        assert_eq!(entry["component"].as_str(), Some("core"));
+        assert_eq!(entry["subject"].as_str(), Some("quote \"it\""));
+        assert_eq!(entry["pr"].as_integer(), Some(482));
+        assert_eq!(entry["closes"][0]["issue"].as_integer(), Some(12));
+        assert_eq!(entry["closes"][1]["issue"].as_str(), Some("PROJ-2"));
+        assert!(entry.get("breaks").is_none());
+    }
+}
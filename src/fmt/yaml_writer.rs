@@ -0,0 +1,198 @@
+use std::io;
+
+use log::debug;
+
+use crate::{
+    clog::Clog,
+    contributors::Contributor,
+    error::Result,
+    fmt::{
+        model::{Changelog, Id, Issue, ReleaseInfo},
+        FormatWriter,
+    },
+    sectionmap::SectionMap,
+};
+
+/// Wraps a `std::io::Write` object to write `clog` output in a YAML format,
+/// using the same structure as the `JsonWriter`
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use clog::{Clog, fmt::YamlWriter};
+/// let clog = Clog::new().unwrap();
+///
+/// // Create a file to hold our results, which the YamlWriter will wrap (note, .unwrap() is only
+/// // used to keep the example short and concise)
+/// let mut file = File::create("my_changelog.yml").ok().unwrap();
+///
+/// // Create the YAML Writer
+/// let mut writer = YamlWriter::new(&mut file);
+///
+/// // Use the YamlWriter to write the changelog
+/// clog.write_changelog_with(&mut writer).unwrap();
+/// ```
+pub struct YamlWriter<'a>(&'a mut dyn io::Write, Option<&'a ReleaseInfo>);
+
+impl<'a> YamlWriter<'a> {
+    /// Creates a new instance of the `YamlWriter` struct using a
+    /// `std::io::Write` object.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::io::{stdout, BufWriter};
+    /// # use clog::{Clog, fmt::YamlWriter};
+    /// let clog = Clog::new().unwrap();
+    ///
+    /// // Create a YamlWriter to wrap stdout
+    /// let out = stdout();
+    /// let mut out_buf = BufWriter::new(out.lock());
+    /// let mut writer = YamlWriter::new(&mut out_buf);
+    /// ```
+    pub fn new<T: io::Write>(writer: &'a mut T) -> YamlWriter<'a> { YamlWriter(writer, None) }
+
+    /// Uses the release values already resolved for several outputs, instead
+    /// of resolving them when writing
+    pub(crate) fn with_release(mut self, release: Option<&'a ReleaseInfo>) -> Self {
+        self.1 = release;
+        self
+    }
+
+    /// Writes the contributors of the release, which are only collected when
+    /// `contributors` is set
+    fn write_contributors(&mut self, contributors: &[Contributor]) -> Result<()> {
+        if contributors.is_empty() {
+            return Ok(());
+        }
+        writeln!(self.0, "contributors:")?;
+        for contributor in contributors {
+            writeln!(self.0, "  - name: {}", quote(&contributor.name))?;
+            writeln!(self.0, "    email: {}", quote(&contributor.email))?;
+            writeln!(self.0, "    first_time: {}", contributor.first_time)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a list of issues belonging to a commit
+    fn write_issues(&mut self, key: &str, issues: &[Issue]) -> Result<()> {
+        if issues.is_empty() {
+            writeln!(self.0, "        {key}: null")?;
+            return Ok(());
+        }
+        writeln!(self.0, "        {key}:")?;
+        for issue in issues {
+            writeln!(self.0, "          - issue: {}", id(&issue.issue))?;
+            writeln!(
+                self.0,
+                "            issue_link: {}",
+                quote(&issue.issue_link)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats an id as a YAML integer, or a double quoted scalar if it isn't a
+/// number
+fn id(id: &Id) -> String {
+    match id {
+        Id::Number(n) => n.to_string(),
+        Id::Key(key) => quote(key),
+    }
+}
+
+/// Formats an optional string as a YAML double quoted scalar, or `null`
+fn opt(s: Option<&str>) -> String { s.map_or_else(|| "null".to_owned(), quote) }
+
+/// Formats a string as a YAML double quoted scalar
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl<'a> FormatWriter for YamlWriter<'a> {
+    fn write_changelog(&mut self, options: &Clog, sm: &SectionMap) -> Result<()> {
+        debug!("Writing YAML changelog");
+        let release = ReleaseInfo::get(self.1, options)?;
+        let changelog = Changelog::new(options, &release, sm);
+        let header = &changelog.header;
+
+        writeln!(self.0, "header:")?;
+        writeln!(self.0, "  version: {}", opt(header.version.as_deref()))?;
+        writeln!(self.0, "  patch_version: {}", header.patch_version)?;
+        writeln!(self.0, "  subtitle: {}", opt(header.subtitle.as_deref()))?;
+        writeln!(self.0, "  date: {}", quote(&header.date))?;
+        writeln!(
+            self.0,
+            "  compare_link: {}",
+            opt(header.compare_link.as_deref())
+        )?;
+
+        if changelog.sections.is_empty() {
+            debug!("There are no sections to write");
+            writeln!(self.0, "sections: null")?;
+            self.write_contributors(&changelog.contributors)?;
+            return self.0.flush().map_err(Into::into);
+        }
+
+        writeln!(self.0, "sections:")?;
+        for section in &changelog.sections {
+            debug!("Writing section: {}", section.title);
+            writeln!(self.0, "  - title: {}", quote(&section.title))?;
+            if section.commits.is_empty() {
+                writeln!(self.0, "    commits: null")?;
+                continue;
+            }
+            writeln!(self.0, "    commits:")?;
+            for entry in &section.commits {
+                writeln!(
+                    self.0,
+                    "      - component: {}",
+                    opt(entry.component.as_deref())
+                )?;
+                writeln!(self.0, "        subject: {}", quote(&entry.subject))?;
+                writeln!(self.0, "        commit_link: {}", quote(&entry.commit_link))?;
+                writeln!(
+                    self.0,
+                    "        pr: {}",
+                    entry.pr.as_ref().map_or_else(|| "null".to_owned(), id)
+                )?;
+                writeln!(self.0, "        pr_link: {}", opt(entry.pr_link.as_deref()))?;
+                self.write_issues("closes", &entry.closes)?;
+                self.write_issues("refs", &entry.refs)?;
+                self.write_issues("breaks", &entry.breaks)?;
+            }
+        }
+        self.write_contributors(&changelog.contributors)?;
+
+        self.0.flush().map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(quote("a \"b\" \\ c"), "\"a \\\"b\\\" \\\\ c\"");
+        assert_eq!(quote("x\ny\u{1b}"), "\"x\\ny\\u001b\"");
+        assert_eq!(opt(None), "null");
+    }
+}
@@ -1,5 +1,9 @@
+use time::OffsetDateTime;
+
+use crate::release_date;
+
 /// The struct representation of a `Commit`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Commit {
     /// The 40 char hash
     pub hash: String,
@@ -9,11 +13,85 @@ pub struct Commit {
     pub component: String,
     /// Any issues this commit closes
     pub closes: Vec<String>,
+    /// Any issues this commit refers to without closing them
+    pub refs: Vec<String>,
     /// Any issues this commit breaks
     pub breaks: Vec<String>,
+    /// The author of the commit, if the `git log` format includes it
+    pub author: Option<Signature>,
+    /// The committer of the commit, if the `git log` format includes it
+    pub committer: Option<Signature>,
+    /// The pull (or merge) request this commit was merged with, taken from a
+    /// squash merge subject like `add pagination (#482)` or a merge commit like
+    /// `Merge pull request #482 from user/branch`
+    pub pr: Option<String>,
     /// The commit type (or alias)
     pub commit_type: String,
+    /// The commit type exactly as written in the subject (i.e. `feat`)
+    pub raw_commit_type: String,
 }
 
 /// A convienience type for multiple commits
 pub type Commits = Vec<Commit>;
+
+/// The name, e-mail and date of the author or committer of a commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// The name, after applying any `.mailmap`
+    pub name: String,
+    /// The e-mail address, after applying any `.mailmap`
+    pub email: String,
+    /// The author date of authors, and the commit date of committers
+    pub date: OffsetDateTime,
+}
+
+/// The `git log` format of the line holding the author and committer of a
+/// commit, whose fields are separated by the unit separator. Dates are in ISO
+/// 8601, so they keep the UTC offset of the author and committer.
+pub(crate) const SIGNATURES_FORMAT: &str = "%aN%x1f%aE%x1f%aI%x1f%cN%x1f%cE%x1f%cI";
+
+/// Parses the author and committer line written by `SIGNATURES_FORMAT`, or
+/// `None` if the line is something else
+pub(crate) fn parse_signatures(line: &str) -> Option<(Option<Signature>, Option<Signature>)> {
+    let fields = line.trim_end().split('\x1f').collect::<Vec<_>>();
+    let [author_name, author_email, author_date, committer_name, committer_email, committer_date] =
+        fields[..]
+    else {
+        return None;
+    };
+    let signature = |name: &str, email: &str, date: &str| {
+        Some(Signature {
+            name: name.to_owned(),
+            email: email.to_owned(),
+            date: release_date::parse_git_date(date).ok()?,
+        })
+    };
+
+    Some((
+        signature(author_name, author_email, author_date),
+        signature(committer_name, committer_email, committer_date),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_signatures() {
+        let (author, committer) = parse_signatures(
+            "Ann\x1fann@example.com\x1f2023-11-15T00:13:20+02:00\x1fBot\x1fbot@example.com\x1fx\n",
+        )
+        .unwrap();
+        let author = author.unwrap();
+        assert_eq!(author.name, "Ann");
+        assert_eq!(author.email, "ann@example.com");
+        assert_eq!(author.date.unix_timestamp(), 1_700_000_000);
+        assert_eq!(author.date.offset().whole_hours(), 2);
+        // An unreadable date drops the signature rather than the commit
+        assert_eq!(committer, None);
+
+        assert!(parse_signatures("feat: no signatures").is_none());
+        assert!(parse_signatures("a\x1fb").is_none());
+    }
+}
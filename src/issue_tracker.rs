@@ -0,0 +1,112 @@
+use regex::Regex;
+
+use crate::{
+    error::{Error, Result},
+    link_style,
+};
+
+/// An external issue tracker, such as Jira, Linear or YouTrack, whose issue
+/// keys are referenced by commits alongside `#123` style issues
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{Clog, IssueTracker};
+/// let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+/// let clog = Clog::new().unwrap().issue_tracker(jira);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IssueTracker {
+    /// The pattern matching the issue keys of the tracker, i.e. `[A-Z]+-\d+`
+    pub pattern: Regex,
+    /// The url template of issues, where `{id}` is replaced by the issue key
+    pub url: String,
+}
+
+impl IssueTracker {
+    /// Creates a new issue tracker from the pattern of its issue keys and the
+    /// url template of its issues
+    ///
+    /// # Errors
+    ///
+    /// If the pattern is not a valid regular expression, or the url template
+    /// uses placeholders other than `{id}`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::IssueTracker;
+    /// let linear = IssueTracker::new(r"ENG-\d+", "https://linear.app/acme/issue/{id}").unwrap();
+    /// ```
+    pub fn new<S: Into<String>>(pattern: &str, url: S) -> Result<IssueTracker> {
+        let url = url.into();
+        link_style::check_template(Some(&url), &["id"])?;
+        let pattern = Regex::new(pattern).map_err(|e| Error::IssuePattern {
+            pattern: pattern.to_owned(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(IssueTracker { pattern, url })
+    }
+
+    /// Whether an issue key, as a whole, belongs to this tracker
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::IssueTracker;
+    /// let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+    ///
+    /// assert!(jira.matches("PROJ-12"));
+    /// assert!(!jira.matches("12"));
+    /// ```
+    pub fn matches(&self, id: &str) -> bool {
+        self.pattern
+            .find(id)
+            .is_some_and(|m| m.start() == 0 && m.end() == id.len())
+    }
+
+    /// Gets the hyperlink url of an issue of this tracker
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::IssueTracker;
+    /// let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+    ///
+    /// assert_eq!(
+    ///     jira.issue_link("PROJ-12"),
+    ///     "https://jira.example/browse/PROJ-12"
+    /// );
+    /// ```
+    pub fn issue_link(&self, id: &str) -> String { self.url.replace("{id}", id) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_whole_keys() {
+        let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+        assert!(jira.matches("PROJ-12"));
+        assert!(!jira.matches("4"));
+        assert!(!jira.matches("xPROJ-12"));
+        assert_eq!(
+            jira.issue_link("PROJ-12"),
+            "https://jira.example/browse/PROJ-12"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_trackers() {
+        assert!(matches!(
+            IssueTracker::new(r"[A-Z+-\d+", "https://jira.example/browse/{id}"),
+            Err(Error::IssuePattern { .. })
+        ));
+        assert!(matches!(
+            IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{issue}"),
+            Err(Error::LinkTemplate { .. })
+        ));
+    }
+}
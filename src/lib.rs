@@ -2,15 +2,37 @@
 
 #[macro_use]
 mod macros;
+mod autolink;
 mod clog;
 mod config;
+mod contributors;
+mod diff;
 pub mod error;
 pub mod fmt;
 pub mod git;
+mod issue_tracker;
 mod link_style;
+mod output;
+mod reference;
+mod release_date;
+mod remote;
 mod sectionmap;
+mod update;
+mod verify;
 
-pub use crate::{clog::Clog, link_style::LinkStyle, sectionmap::SectionMap};
+pub use crate::{
+    clog::Clog,
+    contributors::Contributor,
+    diff::ChangelogDiff,
+    issue_tracker::IssueTracker,
+    link_style::{LinkStyle, LinkTemplates},
+    output::Output,
+    reference::IssueRef,
+    release_date::{DateSource, TimeZone},
+    sectionmap::{ComponentMap, SectionMap},
+    update::ExistingRelease,
+    verify::VerifyReport,
+};
 
 // The default config file
 const DEFAULT_CONFIG_FILE: &str = ".clog.toml";
@@ -2,6 +2,11 @@ use std::str::FromStr;
 
 use strum::{Display, EnumString};
 
+use crate::{
+    error::{Error, Result},
+    reference::{self, IssueRef},
+};
+
 /// Determines the hyperlink style used in commit and issue links. Defaults to
 /// `LinksStyle::Github`
 ///
@@ -20,10 +25,180 @@ pub enum LinkStyle {
     Gitlab,
     Stash,
     Cgit,
+    /// Bitbucket Cloud, i.e. `https://bitbucket.org/owner/repo`
+    Bitbucket,
+    /// Gitea and Forgejo
+    #[strum(serialize = "gitea", serialize = "forgejo")]
+    Gitea,
+    /// Azure DevOps Repos, i.e. `https://dev.azure.com/org/project/_git/repo`,
+    /// where issues link to work items of the project
+    #[strum(serialize = "azure-devops", serialize = "azuredevops")]
+    AzureDevops,
+    /// SourceHut, i.e. `https://git.sr.ht/~owner/repo`, where issues link to
+    /// the tracker of the same name on `todo.sr.ht`
+    Sourcehut,
+}
+
+/// URL templates for forges without a built-in `LinkStyle`, which replace the
+/// link style when set with `Clog::link_templates()`. Templates can use the
+/// placeholders `{repo}`, `{hash}`, `{short_hash}`, `{issue}` and `{pr}` (and
+/// `{from}` and `{to}` for compare links). Links without a template fall back
+/// to plain text, like links without a repository.
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{Clog, LinkTemplates};
+/// let templates = LinkTemplates {
+///     commit: Some("{repo}/c/{hash}".to_owned()),
+///     issue: Some("https://tracker.example/{issue}".to_owned()),
+///     pr: Some("{repo}/merge/{pr}".to_owned()),
+///     compare: Some("{repo}/diff/{from}..{to}".to_owned()),
+/// };
+/// templates.validate().unwrap();
+///
+/// let clog = Clog::new().unwrap().link_templates(templates);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkTemplates {
+    /// The template of commit links
+    pub commit: Option<String>,
+    /// The template of issue links
+    pub issue: Option<String>,
+    /// The template of pull request links
+    pub pr: Option<String>,
+    /// The template of links comparing two commits
+    pub compare: Option<String>,
+}
+
+impl LinkTemplates {
+    /// Checks that each template only uses the placeholders available to it
+    /// and has no unclosed braces
+    pub fn validate(&self) -> Result<()> {
+        check_template(self.commit.as_deref(), &["repo", "hash", "short_hash"])?;
+        check_template(self.issue.as_deref(), &["repo", "issue"])?;
+        check_template(self.pr.as_deref(), &["repo", "pr"])?;
+        check_template(self.compare.as_deref(), &["repo", "from", "to"])
+    }
+
+    /// Whether any template links to the repository
+    pub(crate) fn uses_repo(&self) -> bool {
+        [&self.commit, &self.issue, &self.pr, &self.compare]
+            .iter()
+            .any(|t| t.as_deref().is_some_and(|t| t.contains("{repo}")))
+    }
+
+    /// Gets a hyperlink url to an issue from the issue template
+    pub(crate) fn issue_link(&self, issue: &str, repo: Option<&str>) -> String {
+        self.issue.as_deref().map_or_else(
+            || issue.to_owned(),
+            |t| render(t, &[("repo", repo.unwrap_or_default()), ("issue", issue)]),
+        )
+    }
+
+    /// Gets a hyperlink url to a commit from the commit template
+    pub(crate) fn commit_link(&self, hash: &str, repo: Option<&str>) -> String {
+        let short_hash = hash.get(0..8).unwrap_or(hash);
+        self.commit.as_deref().map_or_else(
+            || short_hash.to_owned(),
+            |t| {
+                render(
+                    t,
+                    &[
+                        ("repo", repo.unwrap_or_default()),
+                        ("hash", hash),
+                        ("short_hash", short_hash),
+                    ],
+                )
+            },
+        )
+    }
+
+    /// Gets a hyperlink url to a view comparing two commits from the compare
+    /// template, or `None` if there is no compare template
+    pub(crate) fn compare_link(&self, from: &str, to: &str, repo: Option<&str>) -> Option<String> {
+        self.compare.as_deref().map(|t| {
+            render(
+                t,
+                &[
+                    ("repo", repo.unwrap_or_default()),
+                    ("from", from),
+                    ("to", to),
+                ],
+            )
+        })
+    }
+
+    /// Gets a hyperlink url to a pull request from the pull request template
+    pub(crate) fn pr_link(&self, pr: &str, repo: Option<&str>) -> String {
+        self.pr.as_deref().map_or_else(
+            || pr.to_owned(),
+            |t| render(t, &[("repo", repo.unwrap_or_default()), ("pr", pr)]),
+        )
+    }
+
+    /// Gets a hyperlink url to any kind of issue reference, like
+    /// `LinkStyle::reference_link()`
+    pub(crate) fn reference_link(&self, reference: IssueRef<'_>, repo: Option<&str>) -> String {
+        match reference {
+            IssueRef::Issue(issue) | IssueRef::Key(issue) => self.issue_link(issue, repo),
+            IssueRef::MergeRequest(mr) => self.pr_link(mr, repo),
+            IssueRef::CrossRepo { repo: other, issue } => match repo {
+                Some(repo) => {
+                    self.issue_link(issue, Some(reference::sibling_repo(repo, other).as_str()))
+                }
+                None => reference.to_string(),
+            },
+        }
+    }
+}
+
+pub(crate) fn check_template(template: Option<&str>, allowed: &[&str]) -> Result<()> {
+    let Some(template) = template else {
+        return Ok(());
+    };
+    let invalid = |reason: String| Error::LinkTemplate {
+        template: template.to_owned(),
+        reason,
+    };
+
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err(invalid("unmatched `}`".to_owned()));
+        }
+        let Some(close) = rest[open..].find('}') else {
+            return Err(invalid("unclosed `{`".to_owned()));
+        };
+        let name = &rest[open + 1..open + close];
+        if !allowed.contains(&name) {
+            return Err(invalid(format!(
+                "unknown placeholder `{{{name}}}`, expected one of {}",
+                allowed
+                    .iter()
+                    .map(|a| format!("`{{{a}}}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        rest = &rest[open + close + 1..];
+    }
+
+    Ok(())
+}
+
+/// Fills in the placeholders of a template
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_owned(), |acc, (name, value)| {
+            acc.replace(&format!("{{{name}}}"), value)
+        })
 }
 
 impl<'de> serde::de::Deserialize<'de> for LinkStyle {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
         D: serde::de::Deserializer<'de>,
     {
@@ -49,7 +224,18 @@ impl LinkStyle {
         if let Some(link) = repo {
             let link = link.as_ref();
             match *self {
-                LinkStyle::Github | LinkStyle::Gitlab => format!("{link}/issues/{issue}"),
+                LinkStyle::Github | LinkStyle::Gitlab | LinkStyle::Bitbucket | LinkStyle::Gitea => {
+                    format!("{link}/issues/{issue}")
+                }
+                LinkStyle::AzureDevops => {
+                    // Work items belong to the project rather than the repository
+                    let project = link.split_once("/_git/").map_or(link, |(p, _)| p);
+                    format!("{project}/_workitems/edit/{issue}")
+                }
+                LinkStyle::Sourcehut => match link.split_once("://git.") {
+                    Some((scheme, rest)) => format!("{scheme}://todo.{rest}/{issue}"),
+                    None => issue.to_string(),
+                },
                 // cgit does not support issues
                 LinkStyle::Stash | LinkStyle::Cgit => issue.to_string(),
             }
@@ -79,12 +265,368 @@ impl LinkStyle {
         if let Some(link) = repo {
             let link = link.as_ref();
             match *self {
-                LinkStyle::Github | LinkStyle::Gitlab => format!("{link}/commit/{hash}"),
-                LinkStyle::Stash => format!("{link}/commits/{hash}"),
+                LinkStyle::Github
+                | LinkStyle::Gitlab
+                | LinkStyle::Gitea
+                | LinkStyle::AzureDevops
+                | LinkStyle::Sourcehut => format!("{link}/commit/{hash}"),
+                LinkStyle::Stash | LinkStyle::Bitbucket => format!("{link}/commits/{hash}"),
                 LinkStyle::Cgit => format!("{link}/commit/?id={hash}"),
             }
         } else {
-            (hash[0..8]).to_string()
+            hash.get(0..8).unwrap_or(hash).to_string()
         }
     }
+
+    /// Gets a hyperlink url to a view comparing two commits (or tags) in the
+    /// specified format, or `None` if there is no repository to link to.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::LinkStyle;
+    /// let link = LinkStyle::Github;
+    /// let compare = link.compare_link(
+    ///     "v1.2.0",
+    ///     "v1.3.0",
+    ///     Some("https://github.com/clog-tool/clog-lib"),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     Some("https://github.com/clog-tool/clog-lib/compare/v1.2.0...v1.3.0".to_owned()),
+    ///     compare
+    /// );
+    /// ```
+    pub fn compare_link<S: AsRef<str>>(&self, from: S, to: S, repo: Option<S>) -> Option<String> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        let link = repo?;
+        let link = link.as_ref();
+        Some(match *self {
+            LinkStyle::Github | LinkStyle::Gitea => format!("{link}/compare/{from}...{to}"),
+            LinkStyle::Gitlab => format!("{link}/-/compare/{from}...{to}"),
+            LinkStyle::Stash => {
+                format!("{link}/compare/diff?targetBranch={from}&sourceBranch={to}")
+            }
+            LinkStyle::Cgit => format!("{link}/diff/?id={to}&id2={from}"),
+            LinkStyle::Bitbucket => format!("{link}/branches/compare/{to}%0D{from}"),
+            LinkStyle::AzureDevops => format!(
+                "{link}/branchCompare?baseVersion={}&targetVersion={}",
+                azure_version(from),
+                azure_version(to)
+            ),
+            // SourceHut has no view comparing two commits
+            LinkStyle::Sourcehut => return None,
+        })
+    }
+
+    /// Gets a hyperlink url to a pull request (or merge request) in the
+    /// specified format.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::LinkStyle;
+    /// let link = LinkStyle::Gitlab;
+    /// let pr = link.pr_link("12", Some("https://gitlab.com/clog-tool/clog-lib"));
+    ///
+    /// assert_eq!(
+    ///     "https://gitlab.com/clog-tool/clog-lib/-/merge_requests/12",
+    ///     pr
+    /// );
+    /// ```
+    pub fn pr_link<S: AsRef<str>>(&self, pr: S, repo: Option<S>) -> String {
+        let pr = pr.as_ref();
+        if let Some(link) = repo {
+            let link = link.as_ref();
+            match *self {
+                LinkStyle::Github => format!("{link}/pull/{pr}"),
+                LinkStyle::Gitlab => format!("{link}/-/merge_requests/{pr}"),
+                LinkStyle::Stash | LinkStyle::Bitbucket => format!("{link}/pull-requests/{pr}"),
+                LinkStyle::Gitea => format!("{link}/pulls/{pr}"),
+                LinkStyle::AzureDevops => format!("{link}/pullrequest/{pr}"),
+                // cgit and SourceHut don't have pull requests
+                LinkStyle::Cgit | LinkStyle::Sourcehut => pr.to_string(),
+            }
+        } else {
+            pr.to_string()
+        }
+    }
+
+    /// Gets the text shown for a pull request, which is `!17` for GitLab merge
+    /// requests and `#17` otherwise
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::LinkStyle;
+    /// assert_eq!(LinkStyle::Github.pr_label("482"), "#482");
+    /// assert_eq!(LinkStyle::Gitlab.pr_label("17"), "!17");
+    /// ```
+    pub fn pr_label(&self, pr: &str) -> String {
+        match self {
+            LinkStyle::Gitlab => format!("!{pr}"),
+            _ => format!("#{pr}"),
+        }
+    }
+
+    /// Gets a hyperlink url to the profile of a user on the forge of the
+    /// repository, or `None` if the forge has no such pages
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::LinkStyle;
+    /// let link = LinkStyle::Github;
+    /// let user = link.user_link("alice", Some("https://github.com/o/r"));
+    ///
+    /// assert_eq!(user.as_deref(), Some("https://github.com/alice"));
+    /// ```
+    pub fn user_link(&self, user: &str, repo: Option<&str>) -> Option<String> {
+        let repo = repo?;
+        match self {
+            LinkStyle::Github | LinkStyle::Gitlab | LinkStyle::Bitbucket | LinkStyle::Gitea => {
+                Some(reference::sibling_repo(repo, user))
+            }
+            LinkStyle::Sourcehut => Some(reference::sibling_repo(repo, &format!("~{user}"))),
+            LinkStyle::Stash | LinkStyle::Cgit | LinkStyle::AzureDevops => None,
+        }
+    }
+
+    /// Gets a hyperlink url to any kind of issue reference, linking issues of
+    /// other repositories to that repository on the same host, and merge
+    /// requests like pull requests.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::{IssueRef, LinkStyle};
+    /// let link = LinkStyle::Gitlab;
+    /// let mr = link.reference_link(IssueRef::parse("!17"), Some("https://gitlab.com/g/r"));
+    ///
+    /// assert_eq!("https://gitlab.com/g/r/-/merge_requests/17", mr);
+    /// ```
+    pub fn reference_link(&self, reference: IssueRef<'_>, repo: Option<&str>) -> String {
+        match reference {
+            IssueRef::Issue(issue) | IssueRef::Key(issue) => self.issue_link(issue, repo),
+            IssueRef::MergeRequest(mr) => self.pr_link(mr, repo),
+            IssueRef::CrossRepo { repo: other, issue } => match repo {
+                Some(repo) => {
+                    self.issue_link(issue, Some(reference::sibling_repo(repo, other).as_str()))
+                }
+                None => reference.to_string(),
+            },
+        }
+    }
+}
+
+/// Azure DevOps versions are prefixed with `GC` for commits and `GT` for tags
+fn azure_version(rev: &str) -> String {
+    if rev.len() >= 7 && rev.bytes().all(|b| b.is_ascii_hexdigit()) {
+        format!("GC{rev}")
+    } else {
+        format!("GT{rev}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_links() {
+        let repo = Some("https://example.com/o/r");
+        assert_eq!(
+            LinkStyle::Github.compare_link("v1", "v2", repo).unwrap(),
+            "https://example.com/o/r/compare/v1...v2"
+        );
+        assert_eq!(
+            LinkStyle::Gitlab.compare_link("v1", "v2", repo).unwrap(),
+            "https://example.com/o/r/-/compare/v1...v2"
+        );
+        assert_eq!(
+            LinkStyle::Stash.compare_link("v1", "v2", repo).unwrap(),
+            "https://example.com/o/r/compare/diff?targetBranch=v1&sourceBranch=v2"
+        );
+        assert_eq!(
+            LinkStyle::Cgit.compare_link("v1", "v2", repo).unwrap(),
+            "https://example.com/o/r/diff/?id=v2&id2=v1"
+        );
+        assert_eq!(LinkStyle::Github.compare_link("v1", "v2", None), None);
+    }
+
+    #[test]
+    fn forge_links() {
+        let hash = "0123456789abcdef0123456789abcdef01234567";
+        let cases = [
+            (
+                LinkStyle::Bitbucket,
+                "https://bitbucket.org/o/r",
+                "https://bitbucket.org/o/r/commits/{hash}",
+                "https://bitbucket.org/o/r/issues/7",
+                "https://bitbucket.org/o/r/pull-requests/8",
+                Some("https://bitbucket.org/o/r/branches/compare/v2%0Dv1"),
+            ),
+            (
+                LinkStyle::Gitea,
+                "https://codeberg.org/o/r",
+                "https://codeberg.org/o/r/commit/{hash}",
+                "https://codeberg.org/o/r/issues/7",
+                "https://codeberg.org/o/r/pulls/8",
+                Some("https://codeberg.org/o/r/compare/v1...v2"),
+            ),
+            (
+                LinkStyle::AzureDevops,
+                "https://dev.azure.com/org/p/_git/r",
+                "https://dev.azure.com/org/p/_git/r/commit/{hash}",
+                "https://dev.azure.com/org/p/_workitems/edit/7",
+                "https://dev.azure.com/org/p/_git/r/pullrequest/8",
+                Some("https://dev.azure.com/org/p/_git/r/branchCompare?baseVersion=GTv1&targetVersion=GTv2"),
+            ),
+            (
+                LinkStyle::Sourcehut,
+                "https://git.sr.ht/~o/r",
+                "https://git.sr.ht/~o/r/commit/{hash}",
+                "https://todo.sr.ht/~o/r/7",
+                "8",
+                None,
+            ),
+            (
+                LinkStyle::Github,
+                "https://github.com/o/r",
+                "https://github.com/o/r/commit/{hash}",
+                "https://github.com/o/r/issues/7",
+                "https://github.com/o/r/pull/8",
+                Some("https://github.com/o/r/compare/v1...v2"),
+            ),
+            (
+                LinkStyle::Gitlab,
+                "https://gitlab.com/o/r",
+                "https://gitlab.com/o/r/commit/{hash}",
+                "https://gitlab.com/o/r/issues/7",
+                "https://gitlab.com/o/r/-/merge_requests/8",
+                Some("https://gitlab.com/o/r/-/compare/v1...v2"),
+            ),
+            (
+                LinkStyle::Stash,
+                "https://stash.example/projects/P/repos/r",
+                "https://stash.example/projects/P/repos/r/commits/{hash}",
+                "7",
+                "https://stash.example/projects/P/repos/r/pull-requests/8",
+                Some("https://stash.example/projects/P/repos/r/compare/diff?targetBranch=v1&sourceBranch=v2"),
+            ),
+            (
+                LinkStyle::Cgit,
+                "https://git.example/r",
+                "https://git.example/r/commit/?id={hash}",
+                "7",
+                "8",
+                Some("https://git.example/r/diff/?id=v2&id2=v1"),
+            ),
+        ];
+        for (style, repo, commit, issue, pr, compare) in cases {
+            let repo = Some(repo);
+            assert_eq!(
+                style.commit_link(hash, repo),
+                commit.replace("{hash}", hash)
+            );
+            assert_eq!(style.issue_link("7", repo), issue, "{style:?}");
+            assert_eq!(style.pr_link("8", repo), pr, "{style:?}");
+            assert_eq!(
+                style.compare_link("v1", "v2", repo).as_deref(),
+                compare,
+                "{style:?}"
+            );
+        }
+        assert_eq!(
+            LinkStyle::AzureDevops
+                .compare_link("v1", hash, Some("https://dev.azure.com/org/p/_git/r"))
+                .unwrap(),
+            format!("https://dev.azure.com/org/p/_git/r/branchCompare?baseVersion=GTv1&targetVersion=GC{hash}")
+        );
+        assert_eq!(LinkStyle::Gitea.pr_link("8", None), "8");
+    }
+
+    #[test]
+    fn reference_links() {
+        let cases = [
+            (
+                LinkStyle::Github,
+                "https://github.com/o/r",
+                "https://github.com/o2/r2/issues/42",
+                "https://github.com/o/r/pull/17",
+            ),
+            (
+                LinkStyle::Gitlab,
+                "https://gitlab.com/g/r",
+                "https://gitlab.com/o2/r2/issues/42",
+                "https://gitlab.com/g/r/-/merge_requests/17",
+            ),
+        ];
+        for (style, repo, cross_repo, mr) in cases {
+            assert_eq!(
+                style.reference_link(IssueRef::parse("o2/r2#42"), Some(repo)),
+                cross_repo
+            );
+            assert_eq!(style.reference_link(IssueRef::parse("!17"), Some(repo)), mr);
+        }
+        assert_eq!(
+            LinkStyle::Github.reference_link(IssueRef::parse("o2/r2#42"), None),
+            "o2/r2#42"
+        );
+    }
+
+    #[test]
+    fn parses_styles() {
+        assert_eq!("forgejo".parse::<LinkStyle>().unwrap(), LinkStyle::Gitea);
+        assert_eq!("Gitea".parse::<LinkStyle>().unwrap(), LinkStyle::Gitea);
+        assert_eq!(
+            "azure-devops".parse::<LinkStyle>().unwrap(),
+            LinkStyle::AzureDevops
+        );
+        assert_eq!(
+            "bitbucket".parse::<LinkStyle>().unwrap(),
+            LinkStyle::Bitbucket
+        );
+        assert_eq!(
+            "SourceHut".parse::<LinkStyle>().unwrap(),
+            LinkStyle::Sourcehut
+        );
+        assert!("custom".parse::<LinkStyle>().is_err());
+    }
+
+    #[test]
+    fn template_links() {
+        let templates = LinkTemplates {
+            commit: Some("{repo}/c/{short_hash}?full={hash}".to_owned()),
+            issue: Some("https://tracker.example/{issue}".to_owned()),
+            pr: Some("{repo}/merge/{pr}".to_owned()),
+            compare: None,
+        };
+        let repo = Some("https://forge.example/r");
+        assert_eq!(
+            templates.commit_link("0123456789abcdef", repo),
+            "https://forge.example/r/c/01234567?full=0123456789abcdef"
+        );
+        assert_eq!(
+            templates.issue_link("42", repo),
+            "https://tracker.example/42"
+        );
+        assert_eq!(
+            templates.reference_link(IssueRef::parse("!3"), repo),
+            "https://forge.example/r/merge/3"
+        );
+        assert_eq!(templates.compare_link("a", "b", repo), None);
+    }
+
+    #[test]
+    fn validates_templates() {
+        let templates = |commit: &str| LinkTemplates {
+            commit: Some(commit.to_owned()),
+            ..LinkTemplates::default()
+        };
+        assert!(templates("{repo}/c/{hash}").validate().is_ok());
+        assert!(templates("{repo}/c/{issue}").validate().is_err());
+        assert!(templates("{repo}/c/{hash").validate().is_err());
+        assert!(templates("{repo}/c/hash}").validate().is_err());
+    }
 }
@@ -0,0 +1,148 @@
+use crate::{config::RawOutputCfg, fmt::ChangelogFormat};
+
+/// A single changelog output, allowing one `clog` run to write the same
+/// release in several formats and files while only collecting the commits
+/// once.
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{fmt::ChangelogFormat, Clog, Output};
+/// let clog = Clog::new()
+///     .unwrap()
+///     .output(Output::new(ChangelogFormat::Markdown).changelog("CHANGELOG.md"))
+///     .output(Output::new(ChangelogFormat::Json).outfile("changelog.json"));
+///
+/// clog.write_changelog().unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Output {
+    /// The format to write this output in
+    pub format: ChangelogFormat,
+    /// The file to read old changelog data from, which will be appended to the
+    /// new data if `prepend` is set (Defaults to the `outfile`)
+    pub infile: Option<String>,
+    /// The file to write this output to (Defaults to `stdout`)
+    pub outfile: Option<String>,
+    /// Whether the old changelog data should be appended after the new data,
+    /// or the output should be overwritten (Defaults to `true`). Only Markdown
+    /// outputs are prepended to, the structured formats whose documents can't
+    /// be concatenated are always overwritten.
+    pub prepend: bool,
+}
+
+impl Output {
+    /// Creates a new output in the given format, which writes to `stdout` until
+    /// an `outfile` is set
+    pub fn new(format: ChangelogFormat) -> Output {
+        Output {
+            format,
+            infile: None,
+            outfile: None,
+            prepend: format.merges(),
+        }
+    }
+
+    /// Sets the file to output or prepend to, it's the equivalent to setting
+    /// both `infile` and `outfile` to the same file
+    #[must_use]
+    pub fn changelog<S: Into<String> + Clone>(mut self, c: S) -> Output {
+        self.infile = Some(c.clone().into());
+        self.outfile = Some(c.into());
+        self
+    }
+
+    /// Sets the file to read old changelog data from
+    #[must_use]
+    pub fn infile<S: Into<String>>(mut self, c: S) -> Output {
+        self.infile = Some(c.into());
+        self
+    }
+
+    /// Sets the file to write this output to
+    #[must_use]
+    pub fn outfile<S: Into<String>>(mut self, c: S) -> Output {
+        self.outfile = Some(c.into());
+        self
+    }
+
+    /// Sets whether old changelog data is kept after the new data, or the
+    /// output is overwritten
+    #[must_use]
+    pub fn prepend(mut self, p: bool) -> Output {
+        self.prepend = p;
+        self
+    }
+}
+
+impl From<RawOutputCfg> for Output {
+    fn from(cfg: RawOutputCfg) -> Self {
+        Output {
+            format: cfg.format,
+            infile: cfg.changelog.clone().or(cfg.infile),
+            outfile: cfg.changelog.or(cfg.outfile),
+            prepend: cfg.prepend.unwrap_or(cfg.format.merges()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use super::*;
+    use crate::Clog;
+
+    #[test]
+    fn prepends_markdown_only() {
+        assert!(Output::new(ChangelogFormat::Markdown).prepend);
+        assert!(!Output::new(ChangelogFormat::Json).prepend);
+        assert!(!Output::new(ChangelogFormat::Ndjson).prepend);
+    }
+
+    #[test]
+    fn overwrites_json_outputs() {
+        let dir = std::env::temp_dir().join(format!("clog-outputs-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("changelog.json");
+
+        // An empty range of the crate's own history is enough to write a release
+        let clog = Clog::default()
+            .git_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.git"))
+            .git_work_tree(env!("CARGO_MANIFEST_DIR"))
+            .from("HEAD")
+            .version("1.0.0")
+            .output(Output::new(ChangelogFormat::Json).changelog(path.to_str().unwrap()));
+        clog.write_changelog().unwrap();
+        clog.write_changelog().unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["header"]["version"], "1.0.0");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overwrites_structured_changelogs() {
+        let dir = std::env::temp_dir().join(format!("clog-structured-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("changelog.toml");
+
+        let clog = Clog::default()
+            .git_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/.git"))
+            .git_work_tree(env!("CARGO_MANIFEST_DIR"))
+            .from("HEAD")
+            .version("1.0.0")
+            .output_format(ChangelogFormat::Toml)
+            .outfile(path.to_str().unwrap());
+        clog.write_changelog_to(&path).unwrap();
+        clog.write_changelog_to(&path).unwrap();
+        clog.write_changelog_from(&path).unwrap();
+
+        let value: toml::Value = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["header"]["version"].as_str(), Some("1.0.0"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::issue_tracker::IssueTracker;
+
+/// The patterns of the references found in commit messages, besides the keys
+/// of external issue trackers: `#12`, `owner/repo#12` and `!12`
+pub(crate) const REF_PATTERNS: [&str; 3] = [r"#\d+", r"~?[\w.-]+(?:/[\w.-]+)+#\d+", r"!\d+"];
+
+/// The kind of an issue reference of a commit, as stored in `Commit::closes`
+/// and `Commit::breaks`
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::IssueRef;
+/// assert_eq!(IssueRef::parse("12"), IssueRef::Issue("12"));
+/// assert_eq!(
+///     IssueRef::parse("other-org/other-repo#42"),
+///     IssueRef::CrossRepo {
+///         repo: "other-org/other-repo",
+///         issue: "42"
+///     }
+/// );
+/// assert_eq!(IssueRef::parse("!17"), IssueRef::MergeRequest("17"));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IssueRef<'a> {
+    /// An issue of the repository, i.e. `#12` (stored as `12`)
+    Issue(&'a str),
+    /// An issue of another repository on the same forge, i.e.
+    /// `other-org/other-repo#42`
+    CrossRepo {
+        /// The path of the other repository
+        repo: &'a str,
+        /// The issue number
+        issue: &'a str,
+    },
+    /// A merge (or pull) request of the repository, i.e. `!17`
+    MergeRequest(&'a str),
+    /// The key of an issue in an external issue tracker, i.e. `PROJ-12`
+    Key(&'a str),
+}
+
+impl<'a> IssueRef<'a> {
+    /// Determines the kind of a stored issue reference
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use clog::IssueRef;
+    /// assert_eq!(IssueRef::parse("PROJ-12"), IssueRef::Key("PROJ-12"));
+    /// ```
+    pub fn parse(reference: &'a str) -> IssueRef<'a> {
+        let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if is_number(reference) {
+            return IssueRef::Issue(reference);
+        }
+        if let Some(mr) = reference.strip_prefix('!').filter(|mr| is_number(mr)) {
+            return IssueRef::MergeRequest(mr);
+        }
+        match reference.rsplit_once('#') {
+            Some((repo, issue)) if repo.contains('/') && is_number(issue) => {
+                IssueRef::CrossRepo { repo, issue }
+            }
+            _ => IssueRef::Key(reference),
+        }
+    }
+}
+
+impl fmt::Display for IssueRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueRef::Issue(issue) => write!(f, "#{issue}"),
+            IssueRef::CrossRepo { repo, issue } => write!(f, "{repo}#{issue}"),
+            IssueRef::MergeRequest(mr) => write!(f, "!{mr}"),
+            IssueRef::Key(key) => f.write_str(key),
+        }
+    }
+}
+
+/// Builds the regex finding a keyword followed by a list of issue references,
+/// i.e. `Fixes: #12, o/r#4, !7, PROJ-3`. Keywords are matched as whole words
+/// regardless of case and may be followed by a colon, with or without a space
+/// before the references (i.e. `Fixes:#12`), while the patterns of issue
+/// trackers stay case-sensitive. The first capture group holds the list.
+pub(crate) fn reference_regex<S: AsRef<str>>(keywords: &[S], trackers: &[IssueTracker]) -> Regex {
+    if keywords.is_empty() {
+        // Nothing can match without any keywords
+        return regex!(r"[^\s\S]");
+    }
+    let keywords = keywords
+        .iter()
+        .map(|k| {
+            k.as_ref()
+                .split_whitespace()
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\s+")
+        })
+        .collect::<Vec<_>>()
+        .join("|");
+    let refs = REF_PATTERNS
+        .iter()
+        .map(|p| (*p).to_owned())
+        .chain(
+            trackers
+                .iter()
+                .map(|t| format!("(?:{})", t.pattern.as_str())),
+        )
+        .collect::<Vec<_>>()
+        .join("|");
+    // The tracker patterns are valid on their own, so the combination is too
+    Regex::new(&format!(
+        r"(?i:\b(?:{keywords})\b):?\s*((?:{refs})(?:\s*,\s*(?:{refs}))*)"
+    ))
+    .expect("issue tracker patterns are valid regexes")
+}
+
+/// Collects the issues of every reference list matched by `regex` in `line`
+/// into `issues`, skipping issues which were already found
+pub(crate) fn extract_into(regex: &Regex, line: &str, issues: &mut Vec<String>) -> bool {
+    let mut found = false;
+    for caps in regex.captures_iter(line) {
+        found = true;
+        for id in issue_ids(&caps[1]) {
+            if !issues.iter().any(|i| i == id) {
+                issues.push(id.to_owned());
+            }
+        }
+    }
+    found
+}
+
+/// Splits a list of issue references into their ids, without the `#` of
+/// plain issue numbers
+fn issue_ids(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(|s| s.trim().trim_start_matches('#'))
+        .filter(|s| !s.is_empty())
+}
+
+/// The url of another repository on the same host as `repo`
+pub(crate) fn sibling_repo(repo: &str, path: &str) -> String {
+    let host_end = repo
+        .find("://")
+        .map(|i| i + 3)
+        .and_then(|start| repo[start..].find('/').map(|end| start + end))
+        .unwrap_or(repo.len());
+    format!("{}/{path}", &repo[..host_end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_references() {
+        assert_eq!(IssueRef::parse("12"), IssueRef::Issue("12"));
+        assert_eq!(IssueRef::parse("!17"), IssueRef::MergeRequest("17"));
+        assert_eq!(
+            IssueRef::parse("grp/sub/proj#3"),
+            IssueRef::CrossRepo {
+                repo: "grp/sub/proj",
+                issue: "3"
+            }
+        );
+        assert_eq!(IssueRef::parse("PROJ-12"), IssueRef::Key("PROJ-12"));
+        assert_eq!(IssueRef::parse("!x"), IssueRef::Key("!x"));
+        for label in ["#12", "o/r#42", "!17", "PROJ-12"] {
+            let stored = label.trim_start_matches('#');
+            assert_eq!(IssueRef::parse(stored).to_string(), label);
+        }
+    }
+
+    fn extract(regex: &Regex, text: &str) -> Vec<String> {
+        let mut issues = vec![];
+        for line in text.lines() {
+            extract_into(regex, line, &mut issues);
+        }
+        issues
+    }
+
+    #[test]
+    fn extracts_every_reference() {
+        let closes = reference_regex(&["closes", "fixes", "fixed", "related to"], &[]);
+        assert_eq!(extract(&closes, "Closes #1, #2, #3"), ["1", "2", "3"]);
+        assert_eq!(
+            extract(
+                &closes,
+                "FIXES: #4,o/r#5 and fixed !6\nRelated  to #7\ncloses #4"
+            ),
+            ["4", "o/r#5", "!6", "7"]
+        );
+        assert_eq!(extract(&closes, "Fixes:#10, closes: #11"), ["10", "11"]);
+        assert!(extract(&closes, "prefixes #8, hotfixes #9, prefixes:#12").is_empty());
+        assert!(extract(&reference_regex::<&str>(&[], &[]), "Closes #1").is_empty());
+
+        let jira = IssueTracker::new(r"[A-Z]+-\d+", "https://jira.example/browse/{id}").unwrap();
+        let closes = reference_regex(&["closes"], &[jira]);
+        assert_eq!(extract(&closes, "Closes PROJ-12, #4"), ["PROJ-12", "4"]);
+        assert!(extract(&closes, "Closes proj-12").is_empty());
+    }
+
+    #[test]
+    fn links_sibling_repos() {
+        assert_eq!(
+            sibling_repo("https://github.com/o/r", "o2/r2"),
+            "https://github.com/o2/r2"
+        );
+        assert_eq!(
+            sibling_repo("https://gitlab.example:8443/g/sub/r", "g/other"),
+            "https://gitlab.example:8443/g/other"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::{env, result::Result as StdResult, str::FromStr};
+
+use strum::{Display, EnumString};
+use time::{format_description::well_known::Rfc3339, Date, Month, OffsetDateTime, UtcOffset};
+use time_tz::{timezones, OffsetDateTimeExt, PrimitiveDateTimeExt, Tz};
+
+use crate::error::{Error, Result};
+
+/// Determines where the date of a release is taken from when no explicit date
+/// was set. Defaults to `DateSource::Now`
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{Clog, DateSource};
+/// let clog = Clog::new().unwrap().date_source(DateSource::Tag);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum DateSource {
+    /// The current time, or `SOURCE_DATE_EPOCH` if it is set for reproducible
+    /// builds
+    #[default]
+    Now,
+    /// The date of the tag of the release (i.e. the `to` commit, the version,
+    /// or the version prefixed with `v`), which is the tagger date of
+    /// annotated tags. Falls back to the date of the `to` commit if there is
+    /// no such tag.
+    Tag,
+    /// The committer date of the `to` commit
+    Commit,
+}
+
+impl<'de> serde::de::Deserialize<'de> for DateSource {
+    fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The time zone release dates are shown in, either a fixed UTC offset or a
+/// named IANA time zone (i.e. `Europe/Berlin`) whose offset follows daylight
+/// saving time. Defaults to UTC.
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{Clog, TimeZone};
+/// let clog = Clog::new()
+///     .unwrap()
+///     .time_zone("Europe/Berlin".parse::<TimeZone>().unwrap());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeZone(Zone);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Zone {
+    Offset(UtcOffset),
+    Named(&'static Tz),
+}
+
+impl TimeZone {
+    /// Coordinated Universal Time
+    pub const UTC: TimeZone = TimeZone(Zone::Offset(UtcOffset::UTC));
+
+    /// Converts a date to this time zone
+    pub(crate) fn convert(self, date: OffsetDateTime) -> OffsetDateTime {
+        match self.0 {
+            Zone::Offset(offset) => date.to_offset(offset),
+            Zone::Named(tz) => date.to_timezone(tz),
+        }
+    }
+
+    /// The start of a day in this time zone. When a daylight saving time
+    /// change skips midnight, the offset before the change is used.
+    pub(crate) fn midnight(self, date: Date) -> OffsetDateTime {
+        match self.0 {
+            Zone::Offset(offset) => date.midnight().assume_offset(offset),
+            Zone::Named(tz) => date
+                .midnight()
+                .assume_timezone(tz)
+                .take_first()
+                .unwrap_or_else(|| date.midnight().assume_timezone_utc(tz)),
+        }
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self { TimeZone::UTC }
+}
+
+impl From<UtcOffset> for TimeZone {
+    fn from(offset: UtcOffset) -> Self { TimeZone(Zone::Offset(offset)) }
+}
+
+impl FromStr for TimeZone {
+    type Err = Error;
+
+    /// Parses an IANA time zone name such as `Europe/Berlin`, or a fixed UTC
+    /// offset such as `+02:00`, `-0530` or `Z`
+    fn from_str(s: &str) -> Result<Self> {
+        match timezones::get_by_name(s.trim()) {
+            Some(tz) => Ok(TimeZone(Zone::Named(tz))),
+            None => parse_offset(s).map(TimeZone::from),
+        }
+    }
+}
+
+/// Reads the date from `SOURCE_DATE_EPOCH`, if it is set
+pub(crate) fn source_date_epoch() -> Result<Option<OffsetDateTime>> {
+    match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) if !epoch.trim().is_empty() => parse_timestamp(&epoch).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Parses a UNIX timestamp in seconds
+pub(crate) fn parse_timestamp(s: &str) -> Result<OffsetDateTime> {
+    let secs = s
+        .trim()
+        .parse()
+        .map_err(|_| Error::InvalidDate(s.to_owned()))?;
+    OffsetDateTime::from_unix_timestamp(secs).map_err(|_| Error::InvalidDate(s.to_owned()))
+}
+
+/// Parses a date written by git in strict ISO 8601 (i.e. `%aI`), keeping its
+/// UTC offset. UNIX timestamps (i.e. `%at`) are accepted too, for custom
+/// `git log` formats.
+pub(crate) fn parse_git_date(s: &str) -> Result<OffsetDateTime> {
+    OffsetDateTime::parse(s.trim(), &Rfc3339).or_else(|_| parse_timestamp(s))
+}
+
+/// Parses an explicit release date, either as `YYYY-MM-DD` (at midnight in the
+/// given time zone) or as a UNIX timestamp
+pub(crate) fn parse_date(s: &str, tz: TimeZone) -> Result<OffsetDateTime> {
+    let invalid = || Error::InvalidDate(s.to_owned());
+    let mut parts = s.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return parse_timestamp(s);
+    };
+    let year = year.parse().map_err(|_| invalid())?;
+    let month = month
+        .parse::<u8>()
+        .ok()
+        .and_then(|m| Month::try_from(m).ok())
+        .ok_or_else(invalid)?;
+    let day = day.parse().map_err(|_| invalid())?;
+
+    Date::from_calendar_date(year, month, day)
+        .map(|date| tz.midnight(date))
+        .map_err(|_| invalid())
+}
+
+/// Parses a fixed UTC offset such as `+02:00`, `-0530` or `Z`
+fn parse_offset(s: &str) -> Result<UtcOffset> {
+    let invalid = || Error::InvalidDate(s.to_owned());
+    let trimmed = s.trim();
+    if trimmed.eq_ignore_ascii_case("z") || trimmed.eq_ignore_ascii_case("utc") {
+        return Ok(UtcOffset::UTC);
+    }
+
+    let (sign, rest) = match trimmed.as_bytes().first() {
+        Some(b'+') => (1, &trimmed[1..]),
+        Some(b'-') => (-1, &trimmed[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest
+        .split_once(':')
+        .or_else(|| {
+            rest.get(2..)
+                .filter(|m| !m.is_empty())
+                .map(|m| (&rest[..2], m))
+        })
+        .unwrap_or((rest, "0"));
+    let hours: i8 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i8 = minutes.parse().map_err(|_| invalid())?;
+
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_offsets() {
+        assert_eq!(parse_offset("Z").unwrap(), UtcOffset::UTC);
+        assert_eq!(
+            parse_offset("+02:00").unwrap(),
+            UtcOffset::from_hms(2, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_offset("-0530").unwrap(),
+            UtcOffset::from_hms(-5, -30, 0).unwrap()
+        );
+        assert_eq!(
+            parse_offset("+9").unwrap(),
+            UtcOffset::from_hms(9, 0, 0).unwrap()
+        );
+        assert!(parse_offset("Europe/Berlin").is_err());
+        assert!(parse_offset("+26:00").is_err());
+    }
+
+    #[test]
+    fn parses_time_zones() {
+        let berlin: TimeZone = "Europe/Berlin".parse().unwrap();
+        // Summer and winter time
+        let summer = OffsetDateTime::from_unix_timestamp(1_719_792_000).unwrap();
+        let winter = OffsetDateTime::from_unix_timestamp(1_704_067_200).unwrap();
+        assert_eq!(berlin.convert(summer).offset().whole_hours(), 2);
+        assert_eq!(berlin.convert(winter).offset().whole_hours(), 1);
+
+        assert_eq!(
+            "+02:00".parse::<TimeZone>().unwrap(),
+            TimeZone::from(UtcOffset::from_hms(2, 0, 0).unwrap())
+        );
+        assert_eq!("Z".parse::<TimeZone>().unwrap(), TimeZone::UTC);
+        assert!("Europe/Nowhere".parse::<TimeZone>().is_err());
+    }
+
+    #[test]
+    fn parses_dates() {
+        let date = parse_date("2024-02-29", TimeZone::UTC).unwrap();
+        assert_eq!(date.unix_timestamp(), 1_709_164_800);
+        assert_eq!(parse_date("1709164800", TimeZone::UTC).unwrap(), date);
+        assert!(parse_date("2023-02-29", TimeZone::UTC).is_err());
+        assert!(parse_date("yesterday", TimeZone::UTC).is_err());
+
+        let date = parse_git_date("2024-02-29T01:00:00+01:00\n").unwrap();
+        assert_eq!(date.unix_timestamp(), 1_709_164_800);
+        assert_eq!(parse_git_date("1709164800").unwrap(), date);
+
+        let berlin = "Europe/Berlin".parse().unwrap();
+        let date = parse_date("2024-07-01", berlin).unwrap();
+        assert_eq!(date.unix_timestamp(), 1_719_784_800);
+    }
+}
@@ -0,0 +1,556 @@
+use std::{
+    borrow::Cow,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    process,
+    result::Result as StdResult,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use log::debug;
+use strum::{Display, EnumString};
+
+use crate::{
+    clog::Clog,
+    error::{Error, Result},
+};
+
+/// Determines what happens when the old changelog already contains a release
+/// with the same version as the one being written. Defaults to
+/// `ExistingRelease::Replace`
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::{Clog, ExistingRelease};
+/// let clog = Clog::new()
+///     .unwrap()
+///     .existing_release(ExistingRelease::Error);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum ExistingRelease {
+    /// Replace the existing release in place
+    #[default]
+    Replace,
+    /// Refuse to write the changelog with `Error::ReleaseExists`
+    Error,
+}
+
+impl<'de> serde::de::Deserialize<'de> for ExistingRelease {
+    fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        FromStr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The anchor of the Unreleased block written when `Clog::unreleased` is set
+/// and there is no version
+pub(crate) const UNRELEASED: &str = "unreleased";
+
+/// Combines a newly generated release with the old changelog contents.
+///
+/// If the old contents contain the start marker (i.e. `<!-- clog:start -->`)
+/// only the region between the start and end markers is updated, and anything
+/// around it is kept as is. If the old contents (or region) already hold a
+/// release of the same version it is replaced in place (or an error is
+/// returned), otherwise the new release is prepended.
+///
+/// When `Clog::unreleased` is set, an existing Unreleased block is always
+/// replaced while there is no version, and removed once there is one since
+/// the new release then takes its place.
+pub(crate) fn merge_release(options: &Clog, new: &str, old: &str) -> Result<String> {
+    if let Some((start, end)) = find_region(old, &options.marker) {
+        debug!("Updating changelog between markers");
+        let region = old[start..end].trim_matches('\n');
+        let merged = merge_release(options, new, region)?;
+        return Ok(format!(
+            "{}\n{}\n\n{}",
+            &old[..start],
+            merged.trim_end_matches('\n'),
+            &old[end..]
+        ));
+    }
+    let version = options.version.as_deref().filter(|v| !v.is_empty());
+    let mut old = Cow::Borrowed(old);
+    if options.unreleased {
+        if let Some(range) = find_release(&old, UNRELEASED) {
+            if version.is_none() {
+                debug!("Replacing the Unreleased block");
+                return Ok(splice(&old, range, new));
+            }
+            debug!("Removing the Unreleased block");
+            old = Cow::Owned(format!("{}{}", &old[..range.0], &old[range.1..]));
+        }
+    }
+    if old.is_empty() {
+        return Ok(new.to_owned());
+    }
+
+    let found = version.and_then(|v| find_release(&old, v).map(|range| (v, range)));
+    match found {
+        Some((v, _)) if options.existing_release == ExistingRelease::Error => {
+            Err(Error::ReleaseExists(v.to_owned()))
+        }
+        Some((_, range)) => Ok(splice(&old, range, new)),
+        None => Ok(format!("{new}\n\n\n{old}")),
+    }
+}
+
+/// Replaces the release at the byte range `(start, end)` of the old contents
+/// with a new release
+fn splice(old: &str, (start, end): (usize, usize), new: &str) -> String {
+    let mut merged = String::with_capacity(old.len() + new.len());
+    merged.push_str(&old[..start]);
+    if end < old.len() {
+        merged.push_str(new.trim_end_matches('\n'));
+        merged.push_str("\n\n\n\n");
+        merged.push_str(&old[end..]);
+    } else {
+        merged.push_str(new);
+    }
+    merged
+}
+
+/// Finds the byte range between the `<!-- {marker}:start -->` and `<!--
+/// {marker}:end -->` markers, excluding the lines holding the markers. If there
+/// is no end marker the region lasts until the end of the contents.
+pub(crate) fn find_region(contents: &str, marker: &str) -> Option<(usize, usize)> {
+    let start_marker = format!("<!-- {marker}:start -->");
+    let end_marker = format!("<!-- {marker}:end -->");
+
+    let mut start = contents.find(&start_marker)? + start_marker.len();
+    start = contents[start..]
+        .find('\n')
+        .map_or(contents.len(), |i| start + i + 1);
+    let end = contents[start..]
+        .find(&end_marker)
+        .map_or(contents.len(), |i| {
+            // End the region at the start of the end marker's line, unless
+            // there is other text before the marker on that line
+            let line_start = contents[start..start + i]
+                .rfind('\n')
+                .map_or(start, |j| start + j + 1);
+            if contents[line_start..start + i].trim().is_empty() {
+                line_start
+            } else {
+                start + i
+            }
+        });
+
+    Some((start, end))
+}
+
+/// Writes `contents` to a temporary file next to `path` and then renames it
+/// over `path`, so `path` always holds either the old or the complete new
+/// contents. When `backup` is set, the previous file is kept as
+/// `{path}.bak`. If `path` is a symlink its target is written, and the link is
+/// kept.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8], backup: bool) -> Result<()> {
+    write_atomic_with(path, backup, |out| {
+        out.write_all(contents).map_err(Into::into)
+    })
+}
+
+/// Like `write_atomic`, but the new contents are written by `f` as they are
+/// produced rather than held in memory first. If `f` fails, `path` is left
+/// untouched.
+pub(crate) fn write_atomic_with<F>(path: &Path, backup: bool, f: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<()>,
+{
+    // Distinguishes the temporary files of several writes in the same process
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let target = match fs::canonicalize(path) {
+        Ok(target) => target,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => path.to_path_buf(),
+        Err(e) => return Err(e.into()),
+    };
+    let name = target.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        )
+    })?;
+    let mut tmp_name = name.to_os_string();
+    tmp_name.push(format!(
+        ".clog-{}-{}.tmp",
+        process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = target.with_file_name(tmp_name);
+
+    let mut out = BufWriter::new(OpenOptions::new().write(true).create_new(true).open(&tmp)?);
+    let res = (|| {
+        f(&mut out)?;
+        let file = out.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        if let Ok(meta) = fs::metadata(&target) {
+            fs::set_permissions(&tmp, meta.permissions())?;
+            if backup {
+                debug!("Backing up {:?}", path);
+                fs::copy(&target, backup_path(path))?;
+            }
+        }
+        fs::rename(&tmp, &target).map_err(Into::into)
+    })();
+    if res.is_err() {
+        fs::remove_file(&tmp).ok();
+    }
+
+    res
+}
+
+/// Reads the contents of an existing changelog, which are empty if the file
+/// doesn't exist yet. Any other error is returned, since writing the new
+/// release without the old contents would lose them.
+pub(crate) fn read_existing(path: &Path) -> Result<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The path the previous version of a file is backed up to
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".bak");
+    name.into()
+}
+
+/// Finds the byte range of the release of `version` in a Markdown changelog,
+/// starting at its `<a name="...">` anchor (or heading, if there is no anchor)
+/// and ending at the first line which is not part of a release written by the
+/// `MarkdownWriter`, which is usually where the next release begins
+pub(crate) fn find_release(contents: &str, version: &str) -> Option<(usize, usize)> {
+    let lines = line_offsets(contents);
+    let anchor = format!("<a name=\"{version}\"></a>");
+    let start = lines
+        .iter()
+        .position(|&(_, l)| l.trim() == anchor)
+        .or_else(|| {
+            lines
+                .iter()
+                .position(|&(_, l)| heading_version(l) == Some(version))
+        })?;
+
+    // The anchor is directly followed by its heading, which must not end the
+    // release
+    let mut next = start + 1;
+    if anchor_version(lines[start].1).is_some()
+        && lines
+            .get(next)
+            .is_some_and(|&(_, l)| heading_version(l).is_some())
+    {
+        next += 1;
+    }
+    let end = lines[next..]
+        .iter()
+        .find(|&&(_, l)| !is_release_body(l))
+        .map_or(contents.len(), |&(offset, _)| offset);
+
+    Some((lines[start].0, end))
+}
+
+/// Whether a line can be part of the body of a release, i.e. is blank, a
+/// section heading or a list entry
+fn is_release_body(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty()
+        || line.starts_with("#### ")
+        || trimmed.starts_with("* ")
+        || trimmed.starts_with("- ")
+        || range_marker(line).is_some()
+}
+
+/// Gets the start and end of the range of commits recorded in a
+/// `<!-- clog: range=from..to -->` comment line, where the start is empty if
+/// the release started at the first commit
+pub(crate) fn range_marker(line: &str) -> Option<(&str, &str)> {
+    line.trim()
+        .strip_prefix("<!-- clog: range=")?
+        .strip_suffix(" -->")?
+        .split_once("..")
+}
+
+/// Gets the end of the newest range of commits recorded in a Markdown
+/// changelog. The range of the Unreleased block is skipped, since the block is
+/// regenerated from the end of the last release rather than from its own end.
+pub(crate) fn last_range_end(contents: &str) -> Option<&str> {
+    let unreleased = find_release(contents, UNRELEASED);
+    line_offsets(contents)
+        .into_iter()
+        .filter(|&(offset, _)| {
+            !unreleased.is_some_and(|(start, end)| (start..end).contains(&offset))
+        })
+        .find_map(|(_, l)| range_marker(l))
+        .map(|(_, to)| to)
+}
+
+/// Gets the version named by an `<a name="...">` anchor line
+pub(crate) fn anchor_version(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix("<a name=\"")?
+        .strip_suffix("\"></a>")
+}
+
+/// Gets the version of a `##` or `###` release heading line
+pub(crate) fn heading_version(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix("### ")
+        .or_else(|| line.strip_prefix("## "))?;
+    // The version may be linked to a compare view, i.e. `[1.1.0](...)`
+    if let Some((version, _)) = rest.strip_prefix('[').and_then(|r| r.split_once("](")) {
+        return Some(version);
+    }
+    rest.split_whitespace().next()
+}
+
+/// Splits some contents into lines, along with the byte offset each line starts
+/// at
+fn line_offsets(contents: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    contents
+        .split_inclusive('\n')
+        .map(|l| {
+            let start = offset;
+            offset += l.len();
+            (start, l.trim_end_matches(['\r', '\n']))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = concat!(
+        "<a name=\"1.1.0\"></a>\n",
+        "## 1.1.0  (2024-02-01)\n\n\n",
+        "#### Features\n\n",
+        "* two ([abcdef12](link))\n\n\n\n",
+        "<a name=\"1.0.0\"></a>\n",
+        "## 1.0.0  (2024-01-01)\n\n\n",
+        "#### Features\n\n",
+        "* one ([12345678](link))\n",
+    );
+
+    #[test]
+    fn prepends_new_release() {
+        let new = "<a name=\"1.2.0\"></a>\n## 1.2.0  (2024-03-01)\n";
+        let merged = merge_release(&Clog::default().version("1.2.0"), new, OLD).unwrap();
+        assert_eq!(merged, format!("{new}\n\n\n{OLD}"));
+    }
+
+    #[test]
+    fn replaces_existing_release() {
+        let new = concat!(
+            "<a name=\"1.1.0\"></a>\n",
+            "## 1.1.0  (2024-02-02)\n\n\n",
+            "#### Features\n\n",
+            "* three ([99999999](link))\n",
+        );
+        let merged = merge_release(&Clog::default().version("1.1.0"), new, OLD).unwrap();
+        assert_eq!(merged.matches("<a name=\"1.1.0\"></a>").count(), 1);
+        assert!(merged.contains("three"));
+        assert!(!merged.contains("two"));
+        assert!(merged.ends_with(&OLD[OLD.find("<a name=\"1.0.0\"").unwrap()..]));
+
+        let merged = merge_release(&Clog::default().version("1.0.0"), new, OLD).unwrap();
+        assert!(merged.contains("two"));
+        assert!(!merged.contains("one"));
+    }
+
+    #[test]
+    fn refuses_existing_release() {
+        let options = Clog::default()
+            .version("1.0.0")
+            .existing_release(ExistingRelease::Error);
+        let res = merge_release(&options, "", OLD);
+        assert!(matches!(res, Err(Error::ReleaseExists(v)) if v == "1.0.0"));
+    }
+
+    #[test]
+    fn maintains_unreleased_block() {
+        let unreleased = "<a name=\"unreleased\"></a>\n## Unreleased\n\n\n#### Features\n\n* new\n";
+        let options = Clog::default().unreleased(true);
+
+        let merged = merge_release(&options, unreleased, OLD).unwrap();
+        assert!(merged.starts_with(unreleased));
+        let again = merge_release(&options, &unreleased.replace("new", "newer"), &merged).unwrap();
+        assert_eq!(again.matches("## Unreleased").count(), 1);
+        assert!(again.contains("newer") && !again.contains("* new\n"));
+
+        let new = "<a name=\"2.0.0\"></a>\n## 2.0.0  (2024-03-03)\n\n\n#### Features\n\n* newer\n";
+        let released = merge_release(&options.version("2.0.0"), new, &again).unwrap();
+        assert!(!released.contains("Unreleased"));
+        assert_eq!(released, format!("{new}\n\n\n{OLD}"));
+    }
+
+    #[test]
+    fn regenerates_unreleased_from_last_release() {
+        let old = OLD.replacen(
+            "(2024-02-01)\n",
+            "(2024-02-01)\n<!-- clog: range=..abc123 -->\n",
+            1,
+        );
+        let unreleased = |to: &str| {
+            format!("<a name=\"unreleased\"></a>\n## Unreleased\n<!-- clog: range=abc123..{to} -->\n\n\n#### Features\n\n* new\n")
+        };
+        let options = Clog::default().unreleased(true);
+        assert_eq!(last_range_end(&old), Some("abc123"));
+
+        let merged = merge_release(&options, &unreleased("def456"), &old).unwrap();
+        assert_eq!(last_range_end(&merged), Some("abc123"));
+        let again = merge_release(&options, &unreleased("def456"), &merged).unwrap();
+        assert_eq!(again, merged);
+        assert_eq!(last_range_end(&again), Some("abc123"));
+    }
+
+    #[test]
+    fn finds_release_with_range() {
+        let old = concat!(
+            "<a name=\"1.0.0\"></a>\n",
+            "## 1.0.0  (2024-01-01)\n",
+            "<!-- clog: range=..abc123 -->\n\n",
+            "* one\n\n",
+            "Hand written notes\n",
+        );
+        assert_eq!(
+            range_marker(old.lines().nth(2).unwrap()),
+            Some(("", "abc123"))
+        );
+        let (start, end) = find_release(old, "1.0.0").unwrap();
+        assert_eq!(&old[end..], "Hand written notes\n");
+        assert_eq!(start, 0);
+    }
+
+    #[test]
+    fn finds_release_by_heading() {
+        let old = "## 2.0.0 Title (2024-01-01)\n\n* a\n\n### 1.9.1 (2023-12-01)\n\n* b\n";
+        assert_eq!(
+            find_release(old, "1.9.1"),
+            Some((old.find("### ").unwrap(), old.len()))
+        );
+        assert_eq!(
+            find_release(old, "2.0.0"),
+            Some((0, old.find("### ").unwrap()))
+        );
+        assert_eq!(find_release(old, "1.9"), None);
+        assert_eq!(
+            heading_version("## [2.1.0](https://example.com/compare/a...b) Title (2024-02-01)"),
+            Some("2.1.0")
+        );
+    }
+
+    #[test]
+    fn keeps_trailing_text() {
+        let old = format!("{OLD}\n\n\nSome hand written history\n");
+        let merged = merge_release(&Clog::default().version("1.1.0"), OLD, &old).unwrap();
+        assert!(merged.ends_with("\n\n\n\nSome hand written history\n"));
+        assert_eq!(merged.matches("<a name=\"1.1.0\"></a>").count(), 1);
+    }
+
+    #[test]
+    fn updates_between_markers() {
+        let old = format!(
+            "# Changelog\n\nIntro\n\n<!-- clog:start -->\n{OLD}<!-- clog:end -->\n\nFooter\n"
+        );
+        let new = "<a name=\"1.2.0\"></a>\n## 1.2.0  (2024-03-01)\n";
+        let merged = merge_release(&Clog::default().version("1.2.0"), new, &old).unwrap();
+        assert!(
+            merged.starts_with("# Changelog\n\nIntro\n\n<!-- clog:start -->\n\n<a name=\"1.2.0\">")
+        );
+        assert!(merged.ends_with("* one ([12345678](link))\n\n<!-- clog:end -->\n\nFooter\n"));
+
+        // Running again for the same version replaces the release inside the region
+        let again = merge_release(&Clog::default().version("1.2.0"), new, &merged).unwrap();
+        assert_eq!(again, merged);
+    }
+
+    #[test]
+    fn custom_marker_without_end() {
+        let old = "# Changelog\n<!-- changes:start -->\n";
+        let options = Clog::default().version("1.0.0").marker("changes");
+        let merged = merge_release(&options, "new\n", old).unwrap();
+        assert_eq!(merged, "# Changelog\n<!-- changes:start -->\n\nnew\n\n");
+        assert_eq!(find_region(old, "clog"), None);
+    }
+
+    #[test]
+    fn atomic_write_with_backup() {
+        let dir = std::env::temp_dir().join(format!("clog-atomic-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("CHANGELOG.md");
+
+        write_atomic(&path, b"first", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        assert!(!backup_path(&path).exists());
+
+        write_atomic(&path, b"second", true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "first");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // A failure midway leaves the old contents in place
+        let res = write_atomic_with(&path, false, |out| {
+            out.write_all(b"partial")?;
+            Err(Error::Git("git log failed".to_owned()))
+        });
+        assert!(res.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // Several writes of the same file at once don't share a temporary file
+        write_atomic_with(&path, false, |out| {
+            write_atomic(&path, b"inner", false)?;
+            out.write_all(b"outer").map_err(Into::into)
+        })
+        .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "outer");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_through_symlink() {
+        let dir = std::env::temp_dir().join(format!("clog-symlink-{}", process::id()));
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        let target = dir.join("docs").join("CHANGELOG.md");
+        let link = dir.join("CHANGELOG.md");
+        fs::write(&target, "old").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, b"new", false).unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reads_existing_changelogs() {
+        let dir = std::env::temp_dir().join(format!("clog-existing-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(read_existing(&dir.join("missing.md")).unwrap(), "");
+        fs::write(dir.join("latin1.md"), b"caf\xe9").unwrap();
+        assert!(read_existing(&dir.join("latin1.md")).is_err());
+        // Reading a directory fails with something else than `NotFound`
+        assert!(read_existing(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
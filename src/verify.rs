@@ -0,0 +1,252 @@
+use std::fmt;
+
+use crate::{autolink, clog::Clog, fmt::parse_releases, git::Commit, sectionmap::SectionMap};
+
+/// The result of verifying an existing Markdown changelog against the release
+/// `clog` would generate from the git history
+///
+/// Entries are compared by their section, component, commit hash, subject, pull
+/// request and referenced issues, ignoring whitespace and the date of the
+/// release.
+///
+/// # Example
+///
+/// ```no_run
+/// # use clog::Clog;
+/// let clog = Clog::new().unwrap().version("1.2.0");
+///
+/// let report = clog.verify_against("changelog.md").unwrap();
+/// if !report.is_ok() {
+///     eprint!("{report}");
+///     std::process::exit(1);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The version of the generated release
+    pub expected_version: Option<String>,
+    /// The version of the release it was compared to, or `None` if the
+    /// changelog doesn't contain any releases
+    pub found_version: Option<String>,
+    /// Commits which are missing from the changelog, with `commit_type` set to
+    /// the section they should be listed in
+    pub missing: Vec<Commit>,
+    /// Entries in the changelog which don't match any commit, with
+    /// `commit_type` set to the section they are listed in
+    pub extra: Vec<Commit>,
+}
+
+impl VerifyReport {
+    /// Whether the changelog contains a release with the expected version
+    pub fn version_matches(&self) -> bool {
+        match (&self.expected_version, &self.found_version) {
+            (Some(expected), Some(found)) => expected == found,
+            (None, found) => found.is_some(),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Whether the changelog is up to date with the git history
+    pub fn is_ok(&self) -> bool {
+        self.version_matches() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.version_matches() {
+            writeln!(
+                f,
+                "expected release {} but found {}",
+                self.expected_version.as_deref().unwrap_or("<none>"),
+                self.found_version.as_deref().unwrap_or("no releases")
+            )?;
+        }
+        for commit in &self.missing {
+            writeln!(f, "missing: {}", describe(commit))?;
+        }
+        for commit in &self.extra {
+            writeln!(f, "extra: {}", describe(commit))?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes a single entry of a report on one line
+fn describe(commit: &Commit) -> String {
+    let short = commit.hash.get(0..8).unwrap_or(&commit.hash);
+    if commit.component.is_empty() {
+        format!("{} {short} {}", commit.commit_type, commit.subject.trim())
+    } else {
+        format!(
+            "{} {short} {}: {}",
+            commit.commit_type,
+            commit.component,
+            commit.subject.trim()
+        )
+    }
+}
+
+/// Compares the release generated from a `SectionMap` against the matching
+/// release in the contents of a Markdown changelog
+pub(crate) fn verify(options: &Clog, sm: &SectionMap, contents: &str) -> VerifyReport {
+    let releases = parse_releases(contents);
+    let release = options
+        .version
+        .as_ref()
+        .and_then(|v| releases.iter().find(|r| &r.version == v))
+        .or_else(|| releases.first());
+
+    // Only the sections known to the options are written by the MarkdownWriter
+    let expected = options.section_map.keys().flat_map(|title| {
+        sm.sections
+            .get(title)
+            .into_iter()
+            .flat_map(|compmap| compmap.values().flatten())
+            .map(move |commit| {
+                let mut commit = in_section(commit, title);
+                // The MarkdownWriter links references in subjects
+                if options.autolink {
+                    commit.subject = autolink::link_subject(options, &commit.subject);
+                }
+                commit
+            })
+    });
+    let mut found: Vec<Commit> = release
+        .map(|r| {
+            r.sections
+                .iter()
+                .flat_map(|(title, compmap)| {
+                    compmap
+                        .values()
+                        .flatten()
+                        .map(move |commit| in_section(commit, title))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut missing = vec![];
+    for commit in expected {
+        if let Some(i) = found.iter().position(|f| same_entry(&commit, f)) {
+            found.remove(i);
+        } else {
+            missing.push(commit);
+        }
+    }
+
+    VerifyReport {
+        expected_version: options.version.clone(),
+        found_version: release.map(|r| r.version.clone()),
+        missing,
+        extra: found,
+    }
+}
+
+fn in_section(commit: &Commit, title: &str) -> Commit {
+    Commit {
+        commit_type: title.to_owned(),
+        ..commit.clone()
+    }
+}
+
+/// Whether a generated commit and an entry read back from a changelog are the
+/// same, where the changelog may only contain an abbreviated hash
+fn same_entry(expected: &Commit, found: &Commit) -> bool {
+    !found.hash.is_empty()
+        && expected.hash.starts_with(&found.hash)
+        && expected.commit_type == found.commit_type
+        && expected.component == found.component
+        && normalize(&expected.subject) == normalize(&found.subject)
+        && expected.pr == found.pr
+        && issues(&expected.closes) == issues(&found.closes)
+        && issues(&expected.refs) == issues(&found.refs)
+        && issues(&expected.breaks) == issues(&found.breaks)
+}
+
+/// Issue numbers without their `#` prefix, skipping empty references
+fn issues(v: &[String]) -> Vec<&str> {
+    v.iter()
+        .map(|s| s.trim().trim_start_matches('#'))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn normalize(s: &str) -> String { s.split_whitespace().collect::<Vec<_>>().join(" ") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmt::{FormatWriter, MarkdownWriter};
+
+    fn commit(hash: &str, ty: &str, component: &str, subject: &str) -> Commit {
+        Commit {
+            hash: hash.repeat(40),
+            subject: subject.to_owned(),
+            component: component.to_owned(),
+            commit_type: ty.to_owned(),
+            ..Commit::default()
+        }
+    }
+
+    fn fixture() -> (Clog, SectionMap, String) {
+        let options = Clog::default().version("1.0.0");
+        let sm = SectionMap::from_commits(vec![
+            commit("a", "Features", "api", " add  pagination"),
+            commit("b", "Bug Fixes", "", " fix crash"),
+            Commit {
+                closes: vec!["12".to_owned()],
+                ..commit("c", "Features", "api", " other")
+            },
+        ]);
+        let mut out = Vec::new();
+        MarkdownWriter::new(&mut out)
+            .write_changelog(&options, &sm)
+            .unwrap();
+        let contents = format!(
+            "{}\n\n\n## 0.9.0 (2020-01-01)\n",
+            String::from_utf8(out).unwrap()
+        );
+        (options, sm, contents)
+    }
+
+    #[test]
+    fn up_to_date() {
+        let (options, sm, contents) = fixture();
+        let report = verify(
+            &options,
+            &sm,
+            &contents.replace("pagination", " pagination "),
+        );
+        assert!(report.is_ok(), "{report}");
+    }
+
+    #[test]
+    fn reports_differences() {
+        let (options, sm, contents) = fixture();
+        let contents = contents
+            .lines()
+            .filter(|l| !l.contains("fix crash"))
+            .map(|l| l.replace("other", "renamed") + "\n")
+            .collect::<String>();
+        let report = verify(&options, &sm, &contents);
+        assert!(report.version_matches());
+        let missing = report
+            .missing
+            .iter()
+            .map(|c| c.subject.trim())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, ["other", "fix crash"]);
+        assert_eq!(report.extra.len(), 1);
+        assert_eq!(report.extra[0].subject, "renamed");
+        assert_eq!(report.extra[0].commit_type, "Features");
+    }
+
+    #[test]
+    fn reports_version() {
+        let (_, sm, contents) = fixture();
+        let report = verify(&Clog::default().version("1.1.0"), &sm, &contents);
+        assert_eq!(report.found_version.as_deref(), Some("1.0.0"));
+        assert!(!report.is_ok());
+    }
+}